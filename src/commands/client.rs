@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+
+use crate::config::Config;
+use crate::connections::Connections;
+use crate::locks::RwLockExt;
+use crate::pubsub::{Message, PubSub};
+use crate::stats::ServerStats;
+use crate::store::Store;
+use crate::store::aof::Aof;
+
+/// What every connection of one server shares.
+#[derive(Debug)]
+pub struct ServerContext {
+    // One store per logical database, indexed by the number clients SELECT
+    pub databases: Arc<[Arc<Store>]>,
+    pub pubsub: Arc<PubSub>,
+    // Every open connection, for commands that inspect or close them
+    pub connections: Arc<Connections>,
+    // Append-only file successful writes are logged to, if enabled
+    pub aof: Option<Arc<Aof>>,
+    pub stats: Arc<ServerStats>,
+    // Password AUTH must be given before other commands run, if any
+    pub requirepass: Option<String>,
+    // Where SAVE and BGSAVE write the snapshot, if persistence is enabled
+    pub snapshot_path: Option<PathBuf>,
+    // Settings the server was started with, updated by CONFIG SET
+    pub config: Arc<RwLock<Config>>,
+}
+
+impl ServerContext {
+    /// The current settings, held for reading until the guard is dropped.
+    pub fn settings(&self) -> RwLockReadGuard<'_, Config> {
+        self.config.read_unpoisoned()
+    }
+}
+
+/// Per-connection state that outlives a single command.
+#[derive(Debug, Clone)]
+pub struct ClientState {
+    // Connection id, which also identifies this client as a subscriber
+    pub id: u64,
+    pub server: Arc<ServerContext>,
+    // Index of the database commands run against
+    pub db: usize,
+    pub authenticated: bool,
+    // Commands queued since MULTI, each as its name followed by its arguments.
+    // None outside a transaction.
+    pub queued: Option<Vec<Vec<String>>>,
+    // Set when a command was rejected while queueing, so EXEC discards the rest
+    pub queue_failed: bool,
+    // Channels this connection is subscribed to
+    pub subscriptions: HashSet<String>,
+    // Where published messages for this connection are delivered
+    pub messages: Sender<Message>,
+    // Set by QUIT so the connection closes after replying
+    pub quit: bool,
+    // RESP version agreed with HELLO
+    pub protocol: u8,
+    // What the running write command goes into the append-only file as, when that
+    // isn't the command as sent. Set by `log_as`.
+    pub logged_as: Option<Vec<Vec<String>>>,
+}
+
+impl ClientState {
+    pub fn new(id: u64, server: Arc<ServerContext>, messages: Sender<Message>) -> Self {
+        Self {
+            id,
+            // Without a password every connection starts out trusted
+            authenticated: server.requirepass.is_none(),
+            server,
+            db: 0,
+            queued: None,
+            queue_failed: false,
+            subscriptions: HashSet::new(),
+            messages,
+            quit: false,
+            protocol: 2,
+            logged_as: None,
+        }
+    }
+
+    /// Logs the running write command to the append-only file as `entries`, each a
+    /// command name followed by its arguments, instead of as it was sent. Commands
+    /// whose effect depends on when they ran use this to log something that replays
+    /// to the same result at any later time.
+    pub fn log_as(&mut self, entries: Vec<Vec<String>>) {
+        self.logged_as = Some(entries);
+    }
+
+    /// Marks the open transaction, if any, as failed because a command in it was
+    /// rejected.
+    pub fn fail_transaction(&mut self) {
+        if self.queued.is_some() {
+            self.queue_failed = true;
+        }
+    }
+
+    /// Leaves the transaction, returning the queued commands and whether any of
+    /// them were rejected.
+    pub fn end_transaction(&mut self) -> Option<(Vec<Vec<String>>, bool)> {
+        let queued = self.queued.take()?;
+        Some((queued, std::mem::take(&mut self.queue_failed)))
+    }
+}
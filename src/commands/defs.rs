@@ -1,64 +1,271 @@
+use std::collections::HashMap;
 use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::OnceLock;
+use std::time::Instant;
 
-use crate::commands::handlers::ping;
-// TODO: Import get handler when implemented
-// use crate::commands::handlers::get;
-// TODO: Import set handler when implemented
-// use crate::commands::handlers::set;
+use crate::commands::client::ClientState;
+use crate::commands::handlers::{
+    append, auth, bgsave, client, command, config, copy, dbsize, debug, decr, decrby, del, discard,
+    exec, exists, expire, flushall, flushdb, get, getrange, getset, hdel, hello, help, hget,
+    hgetall, hincrby, hlen, hset, incr, incrby, info, keys, lindex, llen, lpop, lpush, lrange,
+    lset, mget, mset, multi, object, persist, pexpire, pexpireat, ping, pttl, publish, quit,
+    randomkey, rename, rpop, rpush, sadd, save, scan, scard, sdiff, select, set, setex, setnx,
+    setrange, sinter, sismember, smembers, srem, strlen, subscribe, sunion, touch, ttl, type_,
+};
 use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
 
-#[derive(Clone)]
-pub enum CommandType {
-    Ping,
-    Get,
-    Set,
-}
-
-pub trait CommandHandler {
+pub trait CommandHandler: Sync {
     fn name(&self) -> &'static str;
 
     fn parser(&self) -> ArgumentParser {
         ArgumentParser::new(self.name(), vec![])
     }
 
-    fn execute(&self, args: &ParsedArguments) -> Result<String, ArgumentError>;
+    /// Runs the command against `store`, the client's selected database. `client`
+    /// holds the connection's own state for commands that read or change it.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError>;
+
+    /// Whether the command can change the store. Only these are logged to the
+    /// append-only file.
+    fn is_write(&self) -> bool {
+        false
+    }
+
+    /// Whether the command may run before the connection has authenticated.
+    fn allowed_before_auth(&self) -> bool {
+        false
+    }
+
+    /// Whether the command is queued inside MULTI rather than run immediately.
+    /// Only the commands that control the transaction itself opt out.
+    fn queued_in_transaction(&self) -> bool {
+        true
+    }
+
+    /// Whether the command may be queued inside MULTI at all. EXEC runs the queue
+    /// with the selected database locked, so commands that change which database is
+    /// used or read the others must be refused rather than left to deadlock.
+    fn allowed_in_transaction(&self) -> bool {
+        true
+    }
 
-    fn handle(&self, args: &[&str]) -> Result<String, ArgumentError> {
+    fn handle(
+        &self,
+        args: &[&str],
+        store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
         let parser = self.parser();
         let parsed = parser.parse(args)?;
-        self.execute(&parsed)
+        self.execute(&parsed, store, client)
     }
 }
 
-static PING_HANDLER: ping::PingHandler = ping::PingHandler;
-// TODO: Implement GET_HANDLER
-// static GET_HANDLER: get::GetHandler = get::GetHandler;
-// TODO: Implement SET_HANDLER
-// static SET_HANDLER: set::SetHandler = set::SetHandler;
-
-pub fn match_command(input: &str) -> io::Result<CommandType> {
-    match input.trim().to_lowercase().as_str() {
-        "ping" => Ok(CommandType::Ping),
-        "get" => Ok(CommandType::Get),
-        "set" => Ok(CommandType::Set),
-        _ => Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("Unknown command: {}", input.trim()),
-        )),
+/// Every command the server understands. Adding a handler here is all it takes to
+/// make it dispatchable and listed by HELP.
+pub static COMMANDS: [&dyn CommandHandler; 74] = [
+    &ping::PingHandler,
+    &hello::HelloHandler,
+    &get::GetHandler,
+    &set::SetHandler,
+    &del::DelHandler,
+    &exists::ExistsHandler,
+    &touch::TouchHandler,
+    &expire::ExpireHandler,
+    &ttl::TtlHandler,
+    &pexpire::PexpireHandler,
+    &pexpireat::PexpireatHandler,
+    &pttl::PttlHandler,
+    &persist::PersistHandler,
+    &incr::IncrHandler,
+    &decr::DecrHandler,
+    &incrby::IncrByHandler,
+    &decrby::DecrByHandler,
+    &append::AppendHandler,
+    &keys::KeysHandler,
+    &scan::ScanHandler,
+    &randomkey::RandomkeyHandler,
+    &mget::MgetHandler,
+    &mset::MsetHandler,
+    &type_::TypeHandler,
+    &object::ObjectHandler,
+    &lpush::LpushHandler,
+    &rpush::RpushHandler,
+    &lpop::LpopHandler,
+    &rpop::RpopHandler,
+    &lrange::LrangeHandler,
+    &llen::LlenHandler,
+    &lindex::LindexHandler,
+    &lset::LsetHandler,
+    &hset::HsetHandler,
+    &hget::HgetHandler,
+    &hdel::HdelHandler,
+    &hgetall::HgetallHandler,
+    &hlen::HlenHandler,
+    &hincrby::HincrbyHandler,
+    &sadd::SaddHandler,
+    &srem::SremHandler,
+    &smembers::SmembersHandler,
+    &scard::ScardHandler,
+    &sinter::SinterHandler,
+    &sunion::SunionHandler,
+    &sdiff::SdiffHandler,
+    &sismember::SismemberHandler,
+    &dbsize::DbsizeHandler,
+    &flushall::FlushallHandler,
+    &flushdb::FlushdbHandler,
+    &rename::RenameHandler,
+    &copy::CopyHandler,
+    &strlen::StrlenHandler,
+    &getrange::GetrangeHandler,
+    &setrange::SetrangeHandler,
+    &getset::GetsetHandler,
+    &setnx::SetnxHandler,
+    &setex::SetexHandler,
+    &select::SelectHandler,
+    &auth::AuthHandler,
+    &multi::MultiHandler,
+    &exec::ExecHandler,
+    &discard::DiscardHandler,
+    &subscribe::SubscribeHandler,
+    &publish::PublishHandler,
+    &save::SaveHandler,
+    &bgsave::BgsaveHandler,
+    &info::InfoHandler,
+    &quit::QuitHandler,
+    &client::ClientHandler,
+    &config::ConfigHandler,
+    &help::HelpHandler,
+    &command::CommandHandler,
+    &debug::DebugHandler,
+];
+
+/// Runs `handler`, turning a panic into an error reply so a buggy command fails on
+/// its own instead of taking the connection down with it.
+fn run_isolated(
+    handler: &dyn CommandHandler,
+    args: &[&str],
+    store: &Store,
+    client: &mut ClientState,
+) -> io::Result<Reply> {
+    match panic::catch_unwind(AssertUnwindSafe(|| handler.handle(args, store, client))) {
+        Ok(output) => Ok(output?),
+        Err(payload) => {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown cause");
+            crate::error!("{} panicked -- {}", handler.name(), reason);
+            Err(io::Error::other(format!(
+                "{} failed unexpectedly, see the server log",
+                handler.name()
+            )))
+        }
     }
 }
 
-fn get_handler_for(cmd: &CommandType) -> &'static dyn CommandHandler {
-    match cmd {
-        CommandType::Ping => &PING_HANDLER,
-        // TODO: Implement Get handler
-        CommandType::Get => todo!("Get handler not implemented"),
-        // TODO: Implement Set handler
-        CommandType::Set => todo!("Set handler not implemented"),
-    }
+// Handlers keyed by their uppercase name, built from COMMANDS on first use
+fn registry() -> &'static HashMap<&'static str, &'static dyn CommandHandler> {
+    static REGISTRY: OnceLock<HashMap<&'static str, &'static dyn CommandHandler>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = HashMap::new();
+        for handler in COMMANDS {
+            let previous = registry.insert(handler.name(), handler);
+            assert!(
+                previous.is_none(),
+                "command {} is registered twice",
+                handler.name()
+            );
+        }
+        registry
+    })
 }
 
-pub fn execute(cmd: &CommandType, args: &[&str]) -> io::Result<String> {
-    let handler = get_handler_for(cmd);
-    handler.handle(args).map_err(Into::into)
+/// Looks up a command by name, ignoring case. The handler's `name()` is the single
+/// source of truth, so usage and error text always show the canonical uppercase form.
+pub fn match_command(input: &str) -> io::Result<&'static dyn CommandHandler> {
+    let name = input.trim();
+    registry()
+        .get(name.to_ascii_uppercase().as_str())
+        .copied()
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown command: {}", name),
+            )
+        })
+}
+
+pub fn execute(
+    handler: &dyn CommandHandler,
+    args: &[&str],
+    store: &Store,
+    client: &mut ClientState,
+) -> io::Result<Reply> {
+    if !client.authenticated && !handler.allowed_before_auth() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "NOAUTH Authentication required",
+        ));
+    }
+
+    if client.queued.is_some() && handler.queued_in_transaction() {
+        if !handler.allowed_in_transaction() {
+            client.fail_transaction();
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not allowed inside MULTI", handler.name()),
+            ));
+        }
+
+        // Argument errors show up now, rather than part-way through EXEC
+        if let Err(e) = handler.parser().parse(args) {
+            client.fail_transaction();
+            return Err(e.into());
+        }
+
+        let mut command = vec![handler.name().to_string()];
+        command.extend(args.iter().map(|arg| arg.to_string()));
+        client.queued.get_or_insert_default().push(command);
+        return Ok(Reply::Simple("QUEUED".to_string()));
+    }
+
+    // Failed runs count too; they cost the server time all the same
+    let started = Instant::now();
+    client.logged_as = None;
+    let output = run_isolated(handler, args, store, client);
+    client
+        .server
+        .stats
+        .record_latency(handler.name(), started.elapsed());
+    let output = output?;
+
+    if handler.is_write()
+        && let Some(aof) = &client.server.aof
+    {
+        let entries = client.logged_as.take().unwrap_or_else(|| {
+            let mut entry = vec![handler.name().to_string()];
+            entry.extend(args.iter().map(|arg| arg.to_string()));
+            vec![entry]
+        });
+        // The write has already happened, so a logging failure can't undo it
+        let logged = entries.iter().try_for_each(|entry| {
+            let entry: Vec<&str> = entry.iter().map(String::as_str).collect();
+            aof.append(client.db, &entry)
+        });
+        if let Err(e) = logged {
+            crate::error!("Error writing to the append-only file -- {}", e);
+        }
+    }
+
+    Ok(output)
 }
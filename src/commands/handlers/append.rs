@@ -0,0 +1,42 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct AppendHandler;
+
+impl CommandHandler for AppendHandler {
+    fn name(&self) -> &'static str {
+        "APPEND"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the string to append to")
+            .required_remainder(
+                "value",
+                "Value to append, multiple words are joined by spaces",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let value = args.list("value").join(" ");
+
+        store
+            .append(key, &value)
+            .map(Reply::from)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
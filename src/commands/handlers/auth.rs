@@ -0,0 +1,57 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct AuthHandler;
+
+impl CommandHandler for AuthHandler {
+    fn name(&self) -> &'static str {
+        "AUTH"
+    }
+
+    fn allowed_before_auth(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("password", "Password configured with requirepass")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let password = args.get("password").unwrap_or_default();
+
+        let Some(expected) = &client.server.requirepass else {
+            return Err(self
+                .parser()
+                .error("AUTH called without any password configured"));
+        };
+
+        if !constant_time_eq(password.as_bytes(), expected.as_bytes()) {
+            return Err(self.parser().error("WRONGPASS invalid password"));
+        }
+
+        client.authenticated = true;
+        Ok(Reply::ok())
+    }
+}
+
+/// Compares every byte regardless of where the first difference is, so the time
+/// taken doesn't reveal how much of a guessed password was right.
+fn constant_time_eq(given: &[u8], expected: &[u8]) -> bool {
+    let mut diff = given.len() ^ expected.len();
+    for i in 0..given.len().max(expected.len()) {
+        let a = given.get(i).copied().unwrap_or(0);
+        let b = expected.get(i).copied().unwrap_or(0);
+        diff |= usize::from(a ^ b);
+    }
+    diff == 0
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use std::thread;
+
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{Store, persist};
+
+pub struct BgsaveHandler;
+
+impl CommandHandler for BgsaveHandler {
+    fn name(&self) -> &'static str {
+        "BGSAVE"
+    }
+
+    /// Starts writing the snapshot on its own thread and replies straight away.
+    /// The outcome is only logged.
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let Some(path) = client.server.snapshot_path.clone() else {
+            return Err(self.parser().error("persistence is disabled"));
+        };
+        let databases = Arc::clone(&client.server.databases);
+
+        thread::spawn(move || match persist::save(&path, &databases) {
+            Ok(()) => crate::info!("Background save to {} finished", path.display()),
+            Err(e) => crate::error!("Background save to {} failed -- {}", path.display(), e),
+        });
+
+        Ok(Reply::Simple("Background saving started".to_string()))
+    }
+}
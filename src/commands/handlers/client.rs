@@ -0,0 +1,83 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentDefinition, ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct ClientHandler;
+
+impl CommandHandler for ClientHandler {
+    fn name(&self) -> &'static str {
+        "CLIENT"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .arg(
+                ArgumentDefinition::required("subcommand", "What to do with the connections")
+                    .with_choices(&["LIST", "KILL", "ID"]),
+            )
+            .optional_integer("id", "Connection to close, for KILL")
+            .build()
+    }
+
+    /// LIST shows every open connection, one `field=value` line each, like Redis.
+    /// KILL closes the connection with the given id. ID replies with this
+    /// connection's own id, the one LIST and KILL use.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        match args.get("subcommand") {
+            Some("KILL") => return self.kill(args, client),
+            Some("ID") => return Ok(Reply::Integer(client.id as i64)),
+            _ => {}
+        }
+
+        let lines: Vec<String> = client
+            .server
+            .connections
+            .list()
+            .iter()
+            .map(|summary| {
+                format!(
+                    "id={} addr={} laddr={} status={} age={} idle={}",
+                    summary.id,
+                    summary.address,
+                    summary.endpoint,
+                    summary.status.as_str(),
+                    summary.age.as_secs(),
+                    summary.idle.as_secs()
+                )
+            })
+            .collect();
+        Ok(Reply::bulk(lines.join("\n")))
+    }
+}
+
+impl ClientHandler {
+    fn kill(
+        &self,
+        args: &ParsedArguments,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let Some(id) = args.get_i64("id") else {
+            return Err(self.parser().error("CLIENT KILL needs a connection id"));
+        };
+        let id = u64::try_from(id).map_err(|_| self.parser().error("No such client"))?;
+
+        // Closing our own socket now would lose the reply, so hang up after sending it
+        if id == client.id {
+            client.quit = true;
+            return Ok(Reply::ok());
+        }
+
+        if client.server.connections.kill(id) {
+            Ok(Reply::ok())
+        } else {
+            Err(self.parser().error("No such client"))
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::{self, COMMANDS};
+use crate::commands::parser::{ArgumentDefinition, ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct CommandHandler;
+
+// Named like every other handler, so the trait it implements is reached by path
+impl defs::CommandHandler for CommandHandler {
+    fn name(&self) -> &'static str {
+        "COMMAND"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .arg(
+                ArgumentDefinition::required("subcommand", "What to report about commands")
+                    .with_choices(&["COUNT", "INFO"]),
+            )
+            .optional_remainder("names", "Commands to describe, for INFO")
+            .build()
+    }
+
+    /// COUNT replies with the number of commands. INFO replies with one
+    /// `name=... min=... max=...` line per command asked for, or every command if
+    /// none are named. Arities don't count the command name, and a max of -1 means
+    /// there is no upper bound.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        if args.get("subcommand") == Some("COUNT") {
+            return Ok(Reply::from(COMMANDS.len()));
+        }
+
+        let names = args.list("names");
+        if let Some(unknown) = names.iter().find(|name| {
+            !COMMANDS
+                .iter()
+                .any(|handler| handler.name().eq_ignore_ascii_case(name))
+        }) {
+            return Err(self.parser().error(format!("Unknown command: {}", unknown)));
+        }
+
+        let lines: Vec<String> = COMMANDS
+            .iter()
+            .filter(|handler| {
+                names.is_empty()
+                    || names
+                        .iter()
+                        .any(|name| handler.name().eq_ignore_ascii_case(name))
+            })
+            .map(|handler| {
+                let (min, max) = handler.parser().arity();
+                let max = max.map_or_else(|| "-1".to_string(), |max| max.to_string());
+                format!("name={} min={} max={}", handler.name(), min, max)
+            })
+            .collect();
+        Ok(Reply::list(lines))
+    }
+}
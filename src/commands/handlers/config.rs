@@ -0,0 +1,89 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentDefinition, ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::config;
+use crate::glob::glob_match;
+use crate::locks::RwLockExt;
+use crate::log;
+use crate::store::Store;
+
+pub struct ConfigHandler;
+
+impl CommandHandler for ConfigHandler {
+    fn name(&self) -> &'static str {
+        "CONFIG"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .arg(
+                ArgumentDefinition::required("subcommand", "Whether to read or change settings")
+                    .with_choices(&["GET", "SET"]),
+            )
+            .required(
+                "parameter",
+                "Setting to change, or a glob pattern of settings to read",
+            )
+            .optional_remainder("value", "New value, for SET")
+            .build()
+    }
+
+    /// GET replies with the name and value of every matching setting, alternating
+    /// one per line like Redis. SET changes one setting, if it can change at runtime.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let parameter = args
+            .get("parameter")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if args.get("subcommand") == Some("SET") {
+            return self.set(&parameter, args, client);
+        }
+
+        let settings = client.server.settings();
+        let lines: Vec<String> = config::KEYS
+            .iter()
+            .filter(|key| glob_match(&parameter, key))
+            .flat_map(|key| [key.to_string(), settings.get(key).unwrap_or_default()])
+            .collect();
+        Ok(Reply::list(lines))
+    }
+}
+
+impl ConfigHandler {
+    fn set(
+        &self,
+        parameter: &str,
+        args: &ParsedArguments,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        if args.list("value").is_empty() {
+            return Err(self.parser().error("CONFIG SET needs a value"));
+        }
+        if !config::KEYS.contains(&parameter) {
+            return Err(self
+                .parser()
+                .error(format!("Unknown setting '{}'", parameter)));
+        }
+        if !config::RUNTIME_KEYS.contains(&parameter) {
+            return Err(self.parser().error(format!(
+                "'{}' can only be set before the server starts",
+                parameter
+            )));
+        }
+
+        let mut settings = client.server.config.write_unpoisoned();
+        settings
+            .set(parameter, &args.list("value").join(" "))
+            .map_err(|e| self.parser().error(e.to_string()))?;
+        if parameter == "log_level" {
+            log::set_level(settings.log_level);
+        }
+        Ok(Reply::ok())
+    }
+}
@@ -0,0 +1,52 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct CopyHandler;
+
+impl CommandHandler for CopyHandler {
+    fn name(&self) -> &'static str {
+        "COPY"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("source", "Key to copy")
+            .required("destination", "Key to copy it to")
+            .flag(
+                "replace",
+                "Overwrite the destination if it exists; goes after both keys",
+            )
+            .build()
+    }
+
+    /// Replies 1 if the value and its timeout were copied, or 0 if the source is
+    /// missing or the destination exists without REPLACE. REPLACE is only read after
+    /// both keys, so `COPY a replace` copies to a key named `replace`.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let source = args.get("source").unwrap_or_default();
+        let destination = args.get("destination").unwrap_or_default();
+
+        if source == destination {
+            return Err(self
+                .parser()
+                .error("source and destination are the same key"));
+        }
+
+        let copied = store
+            .copy(source, destination, args.flag("replace"))
+            .map_err(|e| self.parser().error(e.to_string()))?;
+        Ok(Reply::from(copied))
+    }
+}
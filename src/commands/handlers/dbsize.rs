@@ -0,0 +1,22 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct DbsizeHandler;
+
+impl CommandHandler for DbsizeHandler {
+    fn name(&self) -> &'static str {
+        "DBSIZE"
+    }
+
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        Ok(Reply::from(store.size()))
+    }
+}
@@ -0,0 +1,65 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentDefinition, ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct DebugHandler;
+
+impl CommandHandler for DebugHandler {
+    fn name(&self) -> &'static str {
+        "DEBUG"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .arg(
+                ArgumentDefinition::required("subcommand", "Testing aid to run")
+                    .with_choices(&["SLEEP", "PANIC"]),
+            )
+            .optional("seconds", "How long SLEEP blocks this connection, e.g. 0.5")
+            .build()
+    }
+
+    /// SLEEP blocks the thread serving this connection, so tests can hold a worker
+    /// busy or let an idle timeout pass on cue. On its own it holds no lock, but run
+    /// by EXEC it keeps the whole database locked until it returns. PANIC panics, to
+    /// check that a failing command doesn't bring anything else down. Only allowed
+    /// when `enable_debug_command` is set.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        if !client.server.settings().enable_debug_command {
+            return Err(self
+                .parser()
+                .error("DEBUG is disabled; set enable_debug_command to allow it"));
+        }
+
+        if args.get("subcommand") == Some("PANIC") {
+            panic!("DEBUG PANIC requested by client {}", client.id);
+        }
+
+        let Some(seconds) = args.get("seconds") else {
+            return Err(self.parser().error("DEBUG SLEEP needs a number of seconds"));
+        };
+        let duration = seconds
+            .parse::<f64>()
+            .ok()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+            .ok_or_else(|| {
+                self.parser().error(format!(
+                    "seconds must be a non-negative number, got '{}'",
+                    seconds
+                ))
+            })?;
+
+        thread::sleep(duration);
+        Ok(Reply::ok())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::handlers::incr::apply_delta;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct DecrHandler;
+
+impl CommandHandler for DecrHandler {
+    fn name(&self) -> &'static str {
+        "DECR"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the integer to decrement")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        apply_delta(self, args, store, -1)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::handlers::incr::apply_delta;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{Store, StoreError};
+
+pub struct DecrByHandler;
+
+impl CommandHandler for DecrByHandler {
+    fn name(&self) -> &'static str {
+        "DECRBY"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the integer to decrement")
+            .required_integer("amount", "Integer amount to subtract")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        // i64::MIN has no positive counterpart, so it can't be subtracted by negation
+        let delta = args
+            .get_i64("amount")
+            .unwrap_or_default()
+            .checked_neg()
+            .ok_or_else(|| self.parser().error(StoreError::Overflow.to_string()))?;
+
+        apply_delta(self, args, store, delta)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct DelHandler;
+
+impl CommandHandler for DelHandler {
+    fn name(&self) -> &'static str {
+        "DEL"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder("keys", "Keys to remove")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let keys = args.list("keys");
+        let mut data = store.lock_keys(keys.iter().map(String::as_str));
+
+        // A repeated key is only removed, and counted, the first time
+        let count = keys
+            .iter()
+            .filter(|key| data.get_mut(key).remove(key).is_some())
+            .count();
+
+        Ok(Reply::from(count))
+    }
+}
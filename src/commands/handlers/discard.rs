@@ -0,0 +1,29 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct DiscardHandler;
+
+impl CommandHandler for DiscardHandler {
+    fn name(&self) -> &'static str {
+        "DISCARD"
+    }
+
+    fn queued_in_transaction(&self) -> bool {
+        false
+    }
+
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        match client.end_transaction() {
+            Some(_) => Ok(Reply::ok()),
+            None => Err(self.parser().error("DISCARD without MULTI")),
+        }
+    }
+}
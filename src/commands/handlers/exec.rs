@@ -0,0 +1,50 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::{CommandHandler, execute, match_command};
+use crate::commands::parser::{ArgumentError, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct ExecHandler;
+
+impl CommandHandler for ExecHandler {
+    fn name(&self) -> &'static str {
+        "EXEC"
+    }
+
+    fn queued_in_transaction(&self) -> bool {
+        false
+    }
+
+    /// Runs every queued command with the store to itself, so other clients see
+    /// either none of the transaction's writes or all of them. A command that fails
+    /// doesn't stop the ones after it; its error takes its place in the reply.
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let Some((queued, failed)) = client.end_transaction() else {
+            return Err(self.parser().error("EXEC without MULTI"));
+        };
+        if failed {
+            return Err(self
+                .parser()
+                .error("EXECABORT Transaction discarded because of previous errors"));
+        }
+
+        let results = store.exclusive(|store| {
+            queued
+                .iter()
+                .map(|command| {
+                    let args: Vec<&str> = command[1..].iter().map(String::as_str).collect();
+                    match_command(&command[0])
+                        .and_then(|handler| execute(handler, &args, store, client))
+                        .unwrap_or_else(|e| Reply::from_error(&e))
+                })
+                .collect()
+        });
+
+        Ok(Reply::Array(results))
+    }
+}
@@ -0,0 +1,37 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct ExistsHandler;
+
+impl CommandHandler for ExistsHandler {
+    fn name(&self) -> &'static str {
+        "EXISTS"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder("keys", "Keys to check for")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let keys = args.list("keys");
+        let data = store.read_keys(keys.iter().map(String::as_str));
+
+        // Repeated keys are counted every time they appear, like Redis
+        let count = keys
+            .iter()
+            .filter(|key| data.get(key).contains(key))
+            .count();
+
+        Ok(Reply::from(count))
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::{Duration, SystemTime};
+
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+use crate::time;
+
+/// The moment `timeout` from now, or that long ago when `negative`.
+fn deadline_after(timeout: Duration, negative: bool) -> Option<SystemTime> {
+    let now = SystemTime::now();
+    if negative {
+        now.checked_sub(timeout)
+    } else {
+        now.checked_add(timeout)
+    }
+}
+
+/// The PEXPIREAT that sets `key` to expire at `deadline`, for logging an expiry to the
+/// append-only file so a replay keeps the same deadline. Shared by EXPIRE, PEXPIRE
+/// and SETEX.
+pub fn logged_expiry(key: &str, deadline: SystemTime) -> Vec<String> {
+    let millis = time::unix_millis(deadline);
+    vec!["PEXPIREAT".to_string(), key.to_string(), millis.to_string()]
+}
+
+/// Sets the parsed `key` to expire `timeout` from now, or that long ago when
+/// `negative`, and renders whether the key existed. The deadline is logged as it was
+/// applied. Shared by EXPIRE and PEXPIRE.
+pub fn expire_after(
+    handler: &dyn CommandHandler,
+    args: &ParsedArguments,
+    store: &Store,
+    client: &mut ClientState,
+    timeout: Duration,
+    negative: bool,
+) -> Result<Reply, ArgumentError> {
+    let key = args.get("key").unwrap_or_default();
+
+    // A non-positive timeout puts the deadline in the past, expiring the key right away
+    let deadline = deadline_after(timeout, negative)
+        .ok_or_else(|| handler.parser().error("invalid expire time"))?;
+
+    let updated = store.expire_at(key, deadline);
+    client.log_as(vec![logged_expiry(key, deadline)]);
+    Ok(Reply::from(updated))
+}
+
+pub struct ExpireHandler;
+
+impl CommandHandler for ExpireHandler {
+    fn name(&self) -> &'static str {
+        "EXPIRE"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to set a timeout on")
+            .required_integer("seconds", "Seconds until the key expires")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let seconds = args.get_i64("seconds").unwrap_or_default();
+        let timeout = Duration::from_secs(seconds.unsigned_abs());
+        expire_after(self, args, store, client, timeout, seconds < 0)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct FlushallHandler;
+
+impl CommandHandler for FlushallHandler {
+    fn name(&self) -> &'static str {
+        "FLUSHALL"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    // Clearing every database would lock the one EXEC holds
+    fn allowed_in_transaction(&self) -> bool {
+        false
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .one_of(
+                "mode",
+                "Accepted for compatibility; every database is always cleared before replying",
+                &["ASYNC", "SYNC"],
+            )
+            .build()
+    }
+
+    /// Drops every key in every database, one database at a time, so on a large
+    /// keyspace other commands wait until the old data has been freed. It is logged
+    /// to the append-only file as is, so replaying it clears every database too.
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        for db in client.server.databases.iter() {
+            db.clear();
+        }
+        Ok(Reply::ok())
+    }
+}
@@ -0,0 +1,40 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct FlushdbHandler;
+
+impl CommandHandler for FlushdbHandler {
+    fn name(&self) -> &'static str {
+        "FLUSHDB"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .one_of(
+                "mode",
+                "Accepted for compatibility; the database is always cleared before replying",
+                &["ASYNC", "SYNC"],
+            )
+            .build()
+    }
+
+    /// Drops every key in the selected database, leaving the others alone. Each shard
+    /// is locked while it is cleared, so on a large keyspace other commands wait until
+    /// the old data has been freed.
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        store.clear();
+        Ok(Reply::ok())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct GetHandler;
+
+impl CommandHandler for GetHandler {
+    fn name(&self) -> &'static str {
+        "GET"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to read the value of")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let value = store
+            .get(key)
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        Ok(Reply::Bulk(value))
+    }
+}
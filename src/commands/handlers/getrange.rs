@@ -0,0 +1,40 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct GetrangeHandler;
+
+impl CommandHandler for GetrangeHandler {
+    fn name(&self) -> &'static str {
+        "GETRANGE"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the string")
+            .required_integer("start", "First byte offset, negative counts from the end")
+            .required_integer(
+                "end",
+                "Last byte offset (inclusive), negative counts from the end",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let start = args.get_i64("start").unwrap_or_default();
+        let end = args.get_i64("end").unwrap_or_default();
+
+        store
+            .getrange(key, start, end)
+            .map(Reply::bulk)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
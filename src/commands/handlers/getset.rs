@@ -0,0 +1,48 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct GetsetHandler;
+
+impl CommandHandler for GetsetHandler {
+    fn name(&self) -> &'static str {
+        "GETSET"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to store the value under")
+            .required_remainder(
+                "value",
+                "Value to store, multiple words are joined by spaces",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let value = args.list("value").join(" ");
+
+        // Read and write under one lock so no other write lands in between
+        let previous = store
+            .write(key, |data| {
+                let previous = data.get(key)?.cloned();
+                data.set(key, value)?;
+                Ok(previous)
+            })
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        Ok(Reply::Bulk(previous))
+    }
+}
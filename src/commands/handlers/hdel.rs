@@ -0,0 +1,38 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct HdelHandler;
+
+impl CommandHandler for HdelHandler {
+    fn name(&self) -> &'static str {
+        "HDEL"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the hash")
+            .required_remainder("fields", "Fields to remove")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        store
+            .hdel(key, args.list("fields"))
+            .map(Reply::from)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
@@ -0,0 +1,55 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+// Protocol versions HELLO accepts; only RESP2 is spoken so far
+const SUPPORTED_PROTOCOLS: &[i64] = &[2];
+
+pub struct HelloHandler;
+
+impl CommandHandler for HelloHandler {
+    fn name(&self) -> &'static str {
+        "HELLO"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .optional_integer("protover", "RESP version to switch to")
+            .build()
+    }
+
+    /// Switches the connection to the requested protocol version, if supported, and
+    /// replies with alternating field and value lines describing the server.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        if let Some(version) = args.get_i64("protover") {
+            if !SUPPORTED_PROTOCOLS.contains(&version) {
+                return Err(self
+                    .parser()
+                    .error(format!("NOPROTO unsupported protocol version {}", version)));
+            }
+            client.protocol = version as u8;
+        }
+
+        let fields = [
+            ("server", Reply::bulk("rustdes")),
+            ("version", Reply::bulk(env!("CARGO_PKG_VERSION"))),
+            ("proto", Reply::Integer(i64::from(client.protocol))),
+            ("id", Reply::Integer(client.id as i64)),
+            ("mode", Reply::bulk("standalone")),
+            ("role", Reply::bulk("master")),
+        ];
+        Ok(Reply::Array(
+            fields
+                .into_iter()
+                .flat_map(|(field, value)| [Reply::bulk(field), value])
+                .collect(),
+        ))
+    }
+}
@@ -0,0 +1,41 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::{COMMANDS, CommandHandler};
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct HelpHandler;
+
+impl CommandHandler for HelpHandler {
+    fn name(&self) -> &'static str {
+        "HELP"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .optional("command", "Only describe this command")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let wanted = args.get("command");
+
+        let sections: Vec<String> = COMMANDS
+            .iter()
+            .filter(|handler| wanted.is_none_or(|name| handler.name().eq_ignore_ascii_case(name)))
+            .map(|handler| handler.parser().usage_with_details())
+            .collect();
+
+        match wanted {
+            Some(name) if sections.is_empty() => {
+                Err(self.parser().error(format!("Unknown command: {}", name)))
+            }
+            _ => Ok(Reply::bulk(sections.join("\n\n"))),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct HgetHandler;
+
+impl CommandHandler for HgetHandler {
+    fn name(&self) -> &'static str {
+        "HGET"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the hash")
+            .required("field", "Field to read")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let field = args.get("field").unwrap_or_default();
+
+        let value = store
+            .hget(key, field)
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        Ok(Reply::Bulk(value))
+    }
+}
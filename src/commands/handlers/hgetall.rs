@@ -0,0 +1,40 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct HgetallHandler;
+
+impl CommandHandler for HgetallHandler {
+    fn name(&self) -> &'static str {
+        "HGETALL"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the hash")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        let pairs = store
+            .hgetall(key)
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        // Alternating field and value lines
+        let lines: Vec<String> = pairs
+            .into_iter()
+            .flat_map(|(field, value)| [field, value])
+            .collect();
+
+        Ok(Reply::list(lines))
+    }
+}
@@ -0,0 +1,41 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct HincrbyHandler;
+
+impl CommandHandler for HincrbyHandler {
+    fn name(&self) -> &'static str {
+        "HINCRBY"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the hash")
+            .required("field", "Field holding the integer to increment")
+            .required_integer("increment", "Amount to add, negative to subtract")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let field = args.get("field").unwrap_or_default();
+        let increment = args.get_i64("increment").unwrap_or_default();
+
+        store
+            .hincr_by(key, field, increment)
+            .map(Reply::from)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
@@ -0,0 +1,49 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct HsetHandler;
+
+impl CommandHandler for HsetHandler {
+    fn name(&self) -> &'static str {
+        "HSET"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the hash")
+            .required_remainder_with_count("pairs", "Alternating fields and values to set", 2, None)
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let pairs = args.list("pairs");
+        if !pairs.len().is_multiple_of(2) {
+            return Err(self
+                .parser()
+                .error("HSET expects field/value pairs, got an odd number of arguments"));
+        }
+
+        let pairs: Vec<(String, String)> = pairs
+            .chunks(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+
+        store
+            .hset(key, &pairs)
+            .map(Reply::from)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
@@ -0,0 +1,48 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+/// Applies `delta` to the counter at the parsed `key` and renders the new value.
+/// Shared by the INCR/DECR family so they report errors the same way.
+pub fn apply_delta(
+    handler: &dyn CommandHandler,
+    args: &ParsedArguments,
+    store: &Store,
+    delta: i64,
+) -> Result<Reply, ArgumentError> {
+    let key = args.get("key").unwrap_or_default();
+
+    store
+        .incr_by(key, delta)
+        .map(Reply::from)
+        .map_err(|e| handler.parser().error(e.to_string()))
+}
+
+pub struct IncrHandler;
+
+impl CommandHandler for IncrHandler {
+    fn name(&self) -> &'static str {
+        "INCR"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the integer to increment")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        apply_delta(self, args, store, 1)
+    }
+}
@@ -0,0 +1,35 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::handlers::incr::apply_delta;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct IncrByHandler;
+
+impl CommandHandler for IncrByHandler {
+    fn name(&self) -> &'static str {
+        "INCRBY"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the integer to increment")
+            .required_integer("amount", "Integer amount to add")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let amount = args.get_i64("amount").unwrap_or_default();
+        apply_delta(self, args, store, amount)
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::atomic::Ordering;
+
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+use crate::time;
+
+pub struct InfoHandler;
+
+impl CommandHandler for InfoHandler {
+    fn name(&self) -> &'static str {
+        "INFO"
+    }
+
+    // The keyspace section reads every database, including the one EXEC holds
+    fn allowed_in_transaction(&self) -> bool {
+        false
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .one_of(
+                "section",
+                "Only report this section",
+                &[
+                    "server",
+                    "clients",
+                    "stats",
+                    "commandstats",
+                    "keyspace",
+                    "all",
+                ],
+            )
+            .build()
+    }
+
+    /// Reports `field:value` lines grouped under `# Section` headers, like Redis.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let wanted = args.get_or("section", "all");
+        let stats = &client.server.stats;
+        let uptime = time::elapsed_since(stats.started_at).as_secs();
+
+        let mut sections = Vec::new();
+        if matches!(wanted, "server" | "all") {
+            sections.push(format!(
+                "# Server\nuptime_in_seconds:{}\nuptime_in_days:{}",
+                uptime,
+                uptime / 86400
+            ));
+        }
+        if matches!(wanted, "clients" | "all") {
+            sections.push(format!(
+                "# Clients\nconnected_clients:{}",
+                client.server.connections.len()
+            ));
+        }
+        if matches!(wanted, "stats" | "all") {
+            sections.push(format!(
+                "# Stats\ntotal_connections_received:{}\nrejected_connections:{}\ntotal_commands_processed:{}",
+                stats.connections_received.load(Ordering::SeqCst),
+                stats.rejected_connections.load(Ordering::SeqCst),
+                stats.commands_processed.load(Ordering::Relaxed)
+            ));
+        }
+        if matches!(wanted, "commandstats" | "all") {
+            let mut lines = vec!["# Commandstats".to_string()];
+            for (name, command) in stats.command_stats() {
+                lines.push(format!(
+                    "cmdstat_{}:calls={},usec={},usec_per_call={},min_usec={},max_usec={}",
+                    name.to_ascii_lowercase(),
+                    command.calls,
+                    command.total.as_micros(),
+                    command.average().as_micros(),
+                    command.min.as_micros(),
+                    command.max.as_micros()
+                ));
+            }
+            sections.push(lines.join("\n"));
+        }
+        if matches!(wanted, "keyspace" | "all") {
+            let mut lines = vec!["# Keyspace".to_string()];
+            // Like Redis, empty databases are left out
+            for (index, db) in client.server.databases.iter().enumerate() {
+                let keys = db.size();
+                if keys > 0 {
+                    lines.push(format!(
+                        "db{}:keys={},expires={}",
+                        index,
+                        keys,
+                        db.expiring()
+                    ));
+                }
+            }
+            sections.push(lines.join("\n"));
+        }
+
+        Ok(Reply::bulk(sections.join("\n\n")))
+    }
+}
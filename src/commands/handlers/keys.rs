@@ -0,0 +1,35 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct KeysHandler;
+
+impl CommandHandler for KeysHandler {
+    fn name(&self) -> &'static str {
+        "KEYS"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("pattern", "Glob pattern keys must match (*, ?, [...])")
+            .build()
+    }
+
+    /// Scans every key in the store, locking each shard in turn, so this is O(n) in
+    /// the size of the keyspace and holds up writes to a shard while it is read.
+    /// Keys written elsewhere during the scan may or may not be seen.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let pattern = args.get("pattern").unwrap_or_default();
+        let mut keys = store.keys(pattern);
+        keys.sort();
+
+        Ok(Reply::list(keys))
+    }
+}
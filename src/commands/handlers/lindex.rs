@@ -0,0 +1,36 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct LindexHandler;
+
+impl CommandHandler for LindexHandler {
+    fn name(&self) -> &'static str {
+        "LINDEX"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the list")
+            .required_integer("index", "Position to read, negative counts from the end")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let index = args.get_i64("index").unwrap_or_default();
+
+        // Out of range is nil rather than an error, like a missing key
+        store
+            .lindex(key, index)
+            .map(Reply::Bulk)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
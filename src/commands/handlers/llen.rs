@@ -0,0 +1,35 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct LlenHandler;
+
+impl CommandHandler for LlenHandler {
+    fn name(&self) -> &'static str {
+        "LLEN"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the list to count")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        // A missing key is an empty collection
+        store
+            .read(key)
+            .list(key)
+            .map(|value| Reply::from(value.map_or(0, |value| value.len())))
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
@@ -0,0 +1,63 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{ListEnd, Store};
+
+/// Pops from `end` of the list at the parsed `key`. Shared by LPOP and RPOP.
+///
+/// Without `count` the reply is a single element; with it, a list of up to `count`
+/// elements. A missing key replies (nil) either way.
+pub fn pop_from(
+    handler: &dyn CommandHandler,
+    args: &ParsedArguments,
+    store: &Store,
+    end: ListEnd,
+) -> Result<Reply, ArgumentError> {
+    let key = args.get("key").unwrap_or_default();
+    let count = match args.get_i64("count") {
+        Some(count) => Some(
+            usize::try_from(count)
+                .map_err(|_| handler.parser().error("count must be non-negative"))?,
+        ),
+        None => None,
+    };
+
+    let popped = store
+        .pop(key, count.unwrap_or(1), end)
+        .map_err(|e| handler.parser().error(e.to_string()))?;
+
+    Ok(match (popped, count) {
+        (None, _) => Reply::nil(),
+        (Some(values), Some(_)) => Reply::list(values),
+        (Some(values), None) => Reply::Bulk(values.into_iter().next()),
+    })
+}
+
+pub struct LpopHandler;
+
+impl CommandHandler for LpopHandler {
+    fn name(&self) -> &'static str {
+        "LPOP"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the list")
+            .optional_integer("count", "Number of elements to pop from the head")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        pop_from(self, args, store, ListEnd::Left)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{ListEnd, Store};
+
+pub struct LpushHandler;
+
+impl CommandHandler for LpushHandler {
+    fn name(&self) -> &'static str {
+        "LPUSH"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the list")
+            .required_remainder(
+                "values",
+                "Values to push onto the head of the list, in order",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        store
+            .push(key, args.list("values"), ListEnd::Left)
+            .map(Reply::from)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
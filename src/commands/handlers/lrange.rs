@@ -0,0 +1,43 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct LrangeHandler;
+
+impl CommandHandler for LrangeHandler {
+    fn name(&self) -> &'static str {
+        "LRANGE"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the list")
+            .required_integer(
+                "start",
+                "First index to return, negative counts from the end",
+            )
+            .required_integer(
+                "stop",
+                "Last index to return (inclusive), negative counts from the end",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let start = args.get_i64("start").unwrap_or_default();
+        let stop = args.get_i64("stop").unwrap_or_default();
+
+        store
+            .range(key, start, stop)
+            .map(Reply::list)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
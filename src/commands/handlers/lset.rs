@@ -0,0 +1,44 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct LsetHandler;
+
+impl CommandHandler for LsetHandler {
+    fn name(&self) -> &'static str {
+        "LSET"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the list")
+            .required_integer(
+                "index",
+                "Position to overwrite, negative counts from the end",
+            )
+            .required_remainder("value", "New element, multiple words are joined by spaces")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let index = args.get_i64("index").unwrap_or_default();
+        let value = args.list("value").join(" ");
+
+        store
+            .lset(key, index, &value)
+            .map(|()| Reply::ok())
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
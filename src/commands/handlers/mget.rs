@@ -0,0 +1,41 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct MgetHandler;
+
+impl CommandHandler for MgetHandler {
+    fn name(&self) -> &'static str {
+        "MGET"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder("keys", "Keys to read the values of")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let keys = args.list("keys");
+        let data = store.read_keys(keys.iter().map(String::as_str));
+
+        // One line per requested key, in order, so replies map back by position.
+        // Keys holding a non-string value read as (nil) rather than failing the batch.
+        let values = keys
+            .iter()
+            .map(|key| match data.get(key).get(key) {
+                Ok(Some(value)) => Reply::bulk(value.clone()),
+                _ => Reply::nil(),
+            })
+            .collect();
+
+        Ok(Reply::Array(values))
+    }
+}
@@ -1 +1,74 @@
-pub mod ping;
\ No newline at end of file
+pub mod append;
+pub mod auth;
+pub mod bgsave;
+pub mod client;
+pub mod command;
+pub mod config;
+pub mod copy;
+pub mod dbsize;
+pub mod debug;
+pub mod decr;
+pub mod decrby;
+pub mod del;
+pub mod discard;
+pub mod exec;
+pub mod exists;
+pub mod expire;
+pub mod flushall;
+pub mod flushdb;
+pub mod get;
+pub mod getrange;
+pub mod getset;
+pub mod hdel;
+pub mod hello;
+pub mod help;
+pub mod hget;
+pub mod hgetall;
+pub mod hincrby;
+pub mod hlen;
+pub mod hset;
+pub mod incr;
+pub mod incrby;
+pub mod info;
+pub mod keys;
+pub mod lindex;
+pub mod llen;
+pub mod lpop;
+pub mod lpush;
+pub mod lrange;
+pub mod lset;
+pub mod mget;
+pub mod mset;
+pub mod multi;
+pub mod object;
+pub mod persist;
+pub mod pexpire;
+pub mod pexpireat;
+pub mod ping;
+pub mod pttl;
+pub mod publish;
+pub mod quit;
+pub mod randomkey;
+pub mod rename;
+pub mod rpop;
+pub mod rpush;
+pub mod sadd;
+pub mod save;
+pub mod scan;
+pub mod scard;
+pub mod sdiff;
+pub mod select;
+pub mod set;
+pub mod setex;
+pub mod setnx;
+pub mod setrange;
+pub mod sinter;
+pub mod sismember;
+pub mod smembers;
+pub mod srem;
+pub mod strlen;
+pub mod subscribe;
+pub mod sunion;
+pub mod touch;
+pub mod ttl;
+pub mod type_;
@@ -0,0 +1,52 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct MsetHandler;
+
+impl CommandHandler for MsetHandler {
+    fn name(&self) -> &'static str {
+        "MSET"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder_with_count("pairs", "Alternating keys and values to store", 2, None)
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let pairs = args.list("pairs");
+        if !pairs.len().is_multiple_of(2) {
+            return Err(self
+                .parser()
+                .error("MSET expects key/value pairs, got an odd number of arguments"));
+        }
+
+        // Hold one lock for every write so no reader sees a partial update. Room for
+        // every new key is set aside first, so none are left half written.
+        let keys: Vec<&str> = pairs.iter().step_by(2).map(String::as_str).collect();
+        store
+            .write_keys(&keys, &keys, |data| {
+                for pair in pairs.chunks(2) {
+                    data.get_mut(&pair[0])
+                        .set(pair[0].as_str(), pair[1].as_str())?;
+                }
+                Ok(())
+            })
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        Ok(Reply::ok())
+    }
+}
@@ -0,0 +1,31 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct MultiHandler;
+
+impl CommandHandler for MultiHandler {
+    fn name(&self) -> &'static str {
+        "MULTI"
+    }
+
+    fn queued_in_transaction(&self) -> bool {
+        false
+    }
+
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        if client.queued.is_some() {
+            return Err(self.parser().error("MULTI calls can not be nested"));
+        }
+
+        client.queued = Some(Vec::new());
+        Ok(Reply::ok())
+    }
+}
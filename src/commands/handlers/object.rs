@@ -0,0 +1,39 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentDefinition, ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct ObjectHandler;
+
+impl CommandHandler for ObjectHandler {
+    fn name(&self) -> &'static str {
+        "OBJECT"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .arg(
+                ArgumentDefinition::required("subcommand", "What to report about the value")
+                    .with_choices(&["ENCODING"]),
+            )
+            .required("key", "Key holding the value")
+            .build()
+    }
+
+    /// ENCODING names the representation Redis would pick for the value, such as
+    /// `int`, `embstr` or `listpack`.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        store
+            .encoding_of(key)
+            .map(Reply::bulk)
+            .ok_or_else(|| self.parser().error("no such key"))
+    }
+}
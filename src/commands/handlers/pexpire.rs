@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::handlers::expire::expire_after;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct PexpireHandler;
+
+impl CommandHandler for PexpireHandler {
+    fn name(&self) -> &'static str {
+        "PEXPIRE"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to set a timeout on")
+            .required_integer("milliseconds", "Milliseconds until the key expires")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let milliseconds = args.get_i64("milliseconds").unwrap_or_default();
+        let timeout = Duration::from_millis(milliseconds.unsigned_abs());
+        expire_after(self, args, store, client, timeout, milliseconds < 0)
+    }
+}
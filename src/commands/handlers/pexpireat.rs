@@ -0,0 +1,47 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct PexpireatHandler;
+
+impl CommandHandler for PexpireatHandler {
+    fn name(&self) -> &'static str {
+        "PEXPIREAT"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to set a deadline on")
+            .required_integer(
+                "unix-time-milliseconds",
+                "Unix time in milliseconds at which the key expires",
+            )
+            .build()
+    }
+
+    /// A deadline in the past expires the key right away. EXPIRE, PEXPIRE and SETEX
+    /// are logged to the append-only file as this, so replays keep their deadlines.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let millis = args.get_i64("unix-time-milliseconds").unwrap_or_default();
+
+        let deadline = UNIX_EPOCH
+            .checked_add(Duration::from_millis(millis.max(0).unsigned_abs()))
+            .ok_or_else(|| self.parser().error("invalid expire time"))?;
+
+        Ok(Reply::from(store.expire_at(key, deadline)))
+    }
+}
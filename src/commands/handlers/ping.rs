@@ -1,5 +1,11 @@
+use crate::commands::client::ClientState;
 use crate::commands::defs::CommandHandler;
 use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+// Reply to a PING without a message
+const DEFAULT_MESSAGE: &str = "PONG";
 
 pub struct PingHandler;
 
@@ -8,17 +14,99 @@ impl CommandHandler for PingHandler {
         "PING"
     }
 
+    fn allowed_before_auth(&self) -> bool {
+        true
+    }
+
     fn parser(&self) -> ArgumentParser {
         ArgumentParser::builder(self.name())
-            .optional_remainder_with_default(
-                "message",
-                "Custom response to send back to the client",
-                ["PONG"],
-            )
+            .optional_remainder("message", "Custom response to send back to the client")
             .build()
     }
 
-    fn execute(&self, args: &ParsedArguments) -> Result<String, ArgumentError> {
-        Ok(args.list("message").join(" "))
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let message = args.list("message");
+        if message.is_empty() {
+            // A status reply, like Redis, where a message is echoed as a bulk string
+            return Ok(Reply::Simple(DEFAULT_MESSAGE.to_string()));
+        }
+        Ok(Reply::bulk(message.join(" ")))
+    }
+
+    /// A bare PING is the usual health check, so it replies straight away rather than
+    /// going through the parser. The reply matches what `execute` gives with no message.
+    fn handle(
+        &self,
+        args: &[&str],
+        store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        if args.is_empty() {
+            return Ok(Reply::Simple(DEFAULT_MESSAGE.to_string()));
+        }
+        self.execute(&self.parser().parse(args)?, store, client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::sync::{Arc, RwLock};
+
+    use super::*;
+    use crate::commands::client::ServerContext;
+    use crate::config::Config;
+    use crate::connections::Connections;
+    use crate::pubsub::PubSub;
+    use crate::stats::ServerStats;
+
+    fn client() -> ClientState {
+        let server = ServerContext {
+            databases: Arc::new([Arc::new(Store::new())]),
+            pubsub: Arc::new(PubSub::new()),
+            connections: Arc::new(Connections::new()),
+            aof: None,
+            stats: Arc::new(ServerStats::new()),
+            requirepass: None,
+            snapshot_path: None,
+            config: Arc::new(RwLock::new(Config::default())),
+        };
+        ClientState::new(0, Arc::new(server), mpsc::channel().0)
+    }
+
+    #[test]
+    fn a_bare_ping_replies_the_same_with_or_without_the_parser() {
+        let store = Store::new();
+        let mut client = client();
+
+        let fast = PingHandler.handle(&[], &store, &mut client).unwrap();
+        let parsed = PingHandler
+            .execute(
+                &PingHandler.parser().parse(&[]).unwrap(),
+                &store,
+                &mut client,
+            )
+            .unwrap();
+
+        assert_eq!(fast, Reply::Simple("PONG".to_string()));
+        assert_eq!(parsed, fast);
+    }
+
+    #[test]
+    fn a_ping_with_a_message_echoes_it() {
+        let store = Store::new();
+        let mut client = client();
+
+        assert_eq!(
+            PingHandler
+                .handle(&["hello", "there"], &store, &mut client)
+                .unwrap(),
+            Reply::bulk("hello there")
+        );
     }
 }
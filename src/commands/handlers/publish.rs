@@ -0,0 +1,36 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct PublishHandler;
+
+impl CommandHandler for PublishHandler {
+    fn name(&self) -> &'static str {
+        "PUBLISH"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("channel", "Channel to publish to")
+            .required_remainder(
+                "message",
+                "Message to send, multiple words are joined by spaces",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let channel = args.get("channel").unwrap_or_default();
+        let message = args.list("message").join(" ");
+
+        let receivers = client.server.pubsub.publish(channel, &message);
+        Ok(Reply::from(receivers))
+    }
+}
@@ -0,0 +1,32 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct QuitHandler;
+
+impl CommandHandler for QuitHandler {
+    fn name(&self) -> &'static str {
+        "QUIT"
+    }
+
+    fn allowed_before_auth(&self) -> bool {
+        true
+    }
+
+    fn queued_in_transaction(&self) -> bool {
+        false
+    }
+
+    /// Asks the connection to close once this reply has been sent.
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        client.quit = true;
+        Ok(Reply::ok())
+    }
+}
@@ -0,0 +1,22 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct RandomkeyHandler;
+
+impl CommandHandler for RandomkeyHandler {
+    fn name(&self) -> &'static str {
+        "RANDOMKEY"
+    }
+
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        Ok(Reply::Bulk(store.random_key()))
+    }
+}
@@ -0,0 +1,43 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct RenameHandler;
+
+impl CommandHandler for RenameHandler {
+    fn name(&self) -> &'static str {
+        "RENAME"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("source", "Key to rename")
+            .required(
+                "destination",
+                "New name for the key, replacing any value it holds",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let source = args.get("source").unwrap_or_default();
+        let destination = args.get("destination").unwrap_or_default();
+
+        if store.rename(source, destination) {
+            Ok(Reply::ok())
+        } else {
+            Err(self.parser().error("no such key"))
+        }
+    }
+}
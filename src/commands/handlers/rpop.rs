@@ -0,0 +1,34 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::handlers::lpop::pop_from;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{ListEnd, Store};
+
+pub struct RpopHandler;
+
+impl CommandHandler for RpopHandler {
+    fn name(&self) -> &'static str {
+        "RPOP"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the list")
+            .optional_integer("count", "Number of elements to pop from the tail")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        pop_from(self, args, store, ListEnd::Right)
+    }
+}
@@ -0,0 +1,38 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct SaddHandler;
+
+impl CommandHandler for SaddHandler {
+    fn name(&self) -> &'static str {
+        "SADD"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the set")
+            .required_remainder("members", "Members to add")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        store
+            .sadd(key, args.list("members"))
+            .map(Reply::from)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
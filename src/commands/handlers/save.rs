@@ -0,0 +1,38 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{Store, persist};
+
+pub struct SaveHandler;
+
+impl CommandHandler for SaveHandler {
+    fn name(&self) -> &'static str {
+        "SAVE"
+    }
+
+    // Snapshotting locks every database, including the one EXEC holds
+    fn allowed_in_transaction(&self) -> bool {
+        false
+    }
+
+    /// Writes the snapshot before replying. Each database is locked while it is
+    /// written, so this stalls other clients on a large dataset; BGSAVE doesn't.
+    fn execute(
+        &self,
+        _args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let Some(path) = &client.server.snapshot_path else {
+            return Err(self.parser().error("persistence is disabled"));
+        };
+
+        persist::save(path, &client.server.databases).map_err(|e| {
+            self.parser()
+                .error(format!("failed to save snapshot: {}", e))
+        })?;
+
+        Ok(Reply::ok())
+    }
+}
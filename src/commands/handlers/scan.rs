@@ -0,0 +1,59 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::glob::glob_match;
+use crate::store::Store;
+
+// Keys looked at per call when COUNT isn't given, as in Redis
+const DEFAULT_COUNT: i64 = 10;
+
+pub struct ScanHandler;
+
+impl CommandHandler for ScanHandler {
+    fn name(&self) -> &'static str {
+        "SCAN"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_integer(
+                "cursor",
+                "0 to start, then the cursor the last call returned",
+            )
+            .option("match", "Only return keys matching this glob pattern")
+            .option("count", "Roughly how many keys to look at")
+            .build()
+    }
+
+    /// Replies with the next cursor on the first line and a page of keys after it.
+    /// MATCH filters a page after it is taken, so a page can come back empty while
+    /// the walk goes on; only a cursor of 0 means it is over.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let cursor = u64::try_from(args.get_i64("cursor").unwrap_or_default())
+            .map_err(|_| self.parser().error("invalid cursor"))?;
+        let count = match args.get("count") {
+            Some(count) => count.parse::<i64>().unwrap_or(0),
+            None => DEFAULT_COUNT,
+        };
+        let count = usize::try_from(count)
+            .ok()
+            .filter(|count| *count > 0)
+            .ok_or_else(|| self.parser().error("COUNT must be a positive integer"))?;
+
+        let (next, mut keys) = store.scan(cursor, count);
+        if let Some(pattern) = args.get("match") {
+            keys.retain(|key| glob_match(pattern, key));
+        }
+
+        Ok(Reply::Array(vec![
+            Reply::bulk(next.to_string()),
+            Reply::list(keys),
+        ]))
+    }
+}
@@ -0,0 +1,32 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::handlers::sinter::combine;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{SetOperation, Store};
+
+pub struct SdiffHandler;
+
+impl CommandHandler for SdiffHandler {
+    fn name(&self) -> &'static str {
+        "SDIFF"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder(
+                "keys",
+                "Set to start from, followed by the sets whose members are removed",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        combine(self, args, store, SetOperation::Difference)
+    }
+}
@@ -0,0 +1,40 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct SelectHandler;
+
+impl CommandHandler for SelectHandler {
+    fn name(&self) -> &'static str {
+        "SELECT"
+    }
+
+    // The queued commands after it would run against the wrong database
+    fn allowed_in_transaction(&self) -> bool {
+        false
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_integer("index", "Number of the database to switch to")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let index = args.get_i64("index").unwrap_or_default();
+
+        client.db = usize::try_from(index)
+            .ok()
+            .filter(|index| *index < client.server.databases.len())
+            .ok_or_else(|| self.parser().error("DB index is out of range"))?;
+
+        Ok(Reply::ok())
+    }
+}
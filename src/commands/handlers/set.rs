@@ -0,0 +1,44 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct SetHandler;
+
+impl CommandHandler for SetHandler {
+    fn name(&self) -> &'static str {
+        "SET"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to store the value under")
+            .required_remainder(
+                "value",
+                "Value to store, multiple words are joined by spaces",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let value = args.list("value").join(" ");
+
+        // Overwrites any previous value for the key
+        store
+            .set(key, value)
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        Ok(Reply::ok())
+    }
+}
@@ -0,0 +1,64 @@
+use std::time::{Duration, SystemTime};
+
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::handlers::expire::logged_expiry;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct SetexHandler;
+
+impl CommandHandler for SetexHandler {
+    fn name(&self) -> &'static str {
+        "SETEX"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to store the value under")
+            .required_integer("seconds", "Seconds until the key expires, must be positive")
+            .required_remainder(
+                "value",
+                "Value to store, multiple words are joined by spaces",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let seconds = args.get_i64("seconds").unwrap_or_default();
+        let value = args.list("value").join(" ");
+
+        let deadline = u64::try_from(seconds)
+            .ok()
+            .filter(|seconds| *seconds > 0)
+            .and_then(|seconds| SystemTime::now().checked_add(Duration::from_secs(seconds)))
+            .ok_or_else(|| self.parser().error("invalid expire time"))?;
+
+        // Set and expire under one lock so the key is never visible without its TTL
+        store
+            .write(key, |data| {
+                data.set(key, value)?;
+                data.expire_at(key, deadline);
+                Ok(())
+            })
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        // Logged as a SET followed by a PEXPIREAT, so the deadline survives a replay
+        let mut set = vec!["SET".to_string(), key.to_string()];
+        set.extend_from_slice(args.list("value"));
+        client.log_as(vec![set, logged_expiry(key, deadline)]);
+
+        Ok(Reply::ok())
+    }
+}
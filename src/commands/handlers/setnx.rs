@@ -0,0 +1,49 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct SetnxHandler;
+
+impl CommandHandler for SetnxHandler {
+    fn name(&self) -> &'static str {
+        "SETNX"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to store the value under if it doesn't exist")
+            .required_remainder(
+                "value",
+                "Value to store, multiple words are joined by spaces",
+            )
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let value = args.list("value").join(" ");
+
+        // Check and write under one lock so two clients can't both win
+        let created = store
+            .write(key, |data| {
+                if data.contains(key) {
+                    return Ok(false);
+                }
+                data.set(key, value).map(|_| true)
+            })
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        Ok(Reply::from(created))
+    }
+}
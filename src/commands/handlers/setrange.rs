@@ -0,0 +1,54 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+// Longest string SETRANGE may grow a value to, as in Redis
+const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+pub struct SetrangeHandler;
+
+impl CommandHandler for SetrangeHandler {
+    fn name(&self) -> &'static str {
+        "SETRANGE"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the string")
+            .required_integer("offset", "Byte offset to start overwriting at")
+            .required_remainder(
+                "value",
+                "Text to write, multiple words are joined by spaces",
+            )
+            .build()
+    }
+
+    /// Replies with the length of the string after the write.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let value = args.list("value").join(" ");
+        let offset = usize::try_from(args.get_i64("offset").unwrap_or_default())
+            .map_err(|_| self.parser().error("offset is out of range"))?;
+        if offset.saturating_add(value.len()) > MAX_STRING_LEN {
+            return Err(self
+                .parser()
+                .error("string exceeds maximum allowed size (512MB)"));
+        }
+
+        store
+            .setrange(key, offset, &value)
+            .map(Reply::from)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
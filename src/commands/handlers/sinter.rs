@@ -0,0 +1,43 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{SetOperation, Store};
+
+/// Combines the sets at the parsed `keys` with `operation` and renders the members.
+/// Shared by SINTER, SUNION and SDIFF so they report errors the same way.
+pub fn combine(
+    handler: &dyn CommandHandler,
+    args: &ParsedArguments,
+    store: &Store,
+    operation: SetOperation,
+) -> Result<Reply, ArgumentError> {
+    // Members come back sorted; sets themselves have no order
+    store
+        .combine_sets(args.list("keys"), operation)
+        .map(Reply::list)
+        .map_err(|e| handler.parser().error(e.to_string()))
+}
+
+pub struct SinterHandler;
+
+impl CommandHandler for SinterHandler {
+    fn name(&self) -> &'static str {
+        "SINTER"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder("keys", "Sets to intersect")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        combine(self, args, store, SetOperation::Intersection)
+    }
+}
@@ -0,0 +1,36 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct SismemberHandler;
+
+impl CommandHandler for SismemberHandler {
+    fn name(&self) -> &'static str {
+        "SISMEMBER"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the set")
+            .required("member", "Member to look for")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+        let member = args.get("member").unwrap_or_default();
+
+        let found = store
+            .sismember(key, member)
+            .map_err(|e| self.parser().error(e.to_string()))?;
+
+        Ok(Reply::from(found))
+    }
+}
@@ -0,0 +1,34 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct SmembersHandler;
+
+impl CommandHandler for SmembersHandler {
+    fn name(&self) -> &'static str {
+        "SMEMBERS"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the set")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        // Members come back sorted; the set itself has no order
+        store
+            .smembers(key)
+            .map(Reply::list)
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
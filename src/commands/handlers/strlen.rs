@@ -0,0 +1,35 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct StrlenHandler;
+
+impl CommandHandler for StrlenHandler {
+    fn name(&self) -> &'static str {
+        "STRLEN"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key holding the string to measure")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        // Length in bytes, like Redis, so multi-byte characters count more than once
+        store
+            .read(key)
+            .get(key)
+            .map(|value| Reply::from(value.map_or(0, String::len)))
+            .map_err(|e| self.parser().error(e.to_string()))
+    }
+}
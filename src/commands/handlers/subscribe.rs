@@ -0,0 +1,45 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct SubscribeHandler;
+
+impl CommandHandler for SubscribeHandler {
+    fn name(&self) -> &'static str {
+        "SUBSCRIBE"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder("channels", "Channels to receive published messages from")
+            .build()
+    }
+
+    /// Replies with a `subscribe`, channel, subscription count triple per channel.
+    /// Messages published afterwards are pushed to the connection as they arrive.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        _store: &Store,
+        client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let mut replies = Vec::new();
+        for channel in args.list("channels") {
+            client
+                .server
+                .pubsub
+                .subscribe(channel, client.id, client.messages.clone());
+            client.subscriptions.insert(channel.clone());
+
+            replies.push(Reply::Array(vec![
+                Reply::bulk("subscribe"),
+                Reply::bulk(channel.clone()),
+                Reply::from(client.subscriptions.len()),
+            ]));
+        }
+
+        Ok(Reply::Array(replies))
+    }
+}
@@ -0,0 +1,29 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::handlers::sinter::combine;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{SetOperation, Store};
+
+pub struct SunionHandler;
+
+impl CommandHandler for SunionHandler {
+    fn name(&self) -> &'static str {
+        "SUNION"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder("keys", "Sets to merge")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        combine(self, args, store, SetOperation::Union)
+    }
+}
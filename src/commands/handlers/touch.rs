@@ -0,0 +1,38 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::Store;
+
+pub struct TouchHandler;
+
+impl CommandHandler for TouchHandler {
+    fn name(&self) -> &'static str {
+        "TOUCH"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required_remainder("keys", "Keys to mark as just used")
+            .build()
+    }
+
+    /// Counts the keys that exist like EXISTS, but also marks each as just used so
+    /// allkeys-lru evicts it later.
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let keys = args.list("keys");
+        let data = store.read_keys(keys.iter().map(String::as_str));
+
+        let count = keys
+            .iter()
+            .filter(|key| data.get(key).touch_key(key))
+            .count();
+
+        Ok(Reply::from(count))
+    }
+}
@@ -0,0 +1,38 @@
+use crate::commands::client::ClientState;
+use crate::commands::defs::CommandHandler;
+use crate::commands::parser::{ArgumentError, ArgumentParser, ParsedArguments};
+use crate::commands::reply::Reply;
+use crate::store::{Store, Ttl};
+
+pub struct TtlHandler;
+
+impl CommandHandler for TtlHandler {
+    fn name(&self) -> &'static str {
+        "TTL"
+    }
+
+    fn parser(&self) -> ArgumentParser {
+        ArgumentParser::builder(self.name())
+            .required("key", "Key to report the remaining time to live of")
+            .build()
+    }
+
+    fn execute(
+        &self,
+        args: &ParsedArguments,
+        store: &Store,
+        _client: &mut ClientState,
+    ) -> Result<Reply, ArgumentError> {
+        let key = args.get("key").unwrap_or_default();
+
+        // -2 for a missing (or expired) key, -1 for a key without a timeout
+        let reply = match store.ttl(key) {
+            Ttl::Missing => -2,
+            Ttl::Persistent => -1,
+            // Round to the nearest second like Redis does
+            Ttl::Expires(remaining) => ((remaining.as_millis() + 500) / 1000) as i64,
+        };
+
+        Ok(Reply::Integer(reply))
+    }
+}
@@ -1,6 +1,7 @@
+pub mod client;
 pub mod defs;
 pub mod parser;
+pub mod reply;
+pub mod tokenize;
 
 mod handlers;
-
-
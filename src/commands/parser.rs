@@ -1,4 +1,4 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
 use std::fmt;
 use std::io;
@@ -7,6 +7,17 @@ use std::io;
 pub enum ArgumentArity {
     Single,
     Remainder,
+    /// A boolean `--name` switch, accepted anywhere in the arguments
+    Flag,
+    /// A `--name value` option, accepted anywhere in the arguments
+    Named,
+}
+
+/// The kind of value an argument accepts, checked by `ArgumentParser::parse`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgumentType {
+    Text,
+    Integer,
 }
 
 #[derive(Clone, Debug)]
@@ -16,6 +27,12 @@ pub struct ArgumentDefinition {
     pub required: bool,
     pub arity: ArgumentArity,
     pub default: Option<Vec<String>>,
+    pub value_type: ArgumentType,
+    /// Keywords the value must be one of, compared case-insensitively
+    pub choices: Option<Vec<&'static str>>,
+    /// Bounds on how many values a remainder argument may capture when given
+    pub min_count: usize,
+    pub max_count: Option<usize>,
 }
 
 impl ArgumentDefinition {
@@ -26,6 +43,10 @@ impl ArgumentDefinition {
             required: true,
             arity: ArgumentArity::Single,
             default: None,
+            value_type: ArgumentType::Text,
+            choices: None,
+            min_count: 0,
+            max_count: None,
         }
     }
 
@@ -36,6 +57,10 @@ impl ArgumentDefinition {
             required: false,
             arity: ArgumentArity::Single,
             default: None,
+            value_type: ArgumentType::Text,
+            choices: None,
+            min_count: 0,
+            max_count: None,
         }
     }
 
@@ -50,6 +75,10 @@ impl ArgumentDefinition {
             required: false,
             arity: ArgumentArity::Single,
             default: Some(vec![default.into()]),
+            value_type: ArgumentType::Text,
+            choices: None,
+            min_count: 0,
+            max_count: None,
         }
     }
 
@@ -60,6 +89,10 @@ impl ArgumentDefinition {
             required: true,
             arity: ArgumentArity::Remainder,
             default: None,
+            value_type: ArgumentType::Text,
+            choices: None,
+            min_count: 0,
+            max_count: None,
         }
     }
 
@@ -70,6 +103,10 @@ impl ArgumentDefinition {
             required: false,
             arity: ArgumentArity::Remainder,
             default: None,
+            value_type: ArgumentType::Text,
+            choices: None,
+            min_count: 0,
+            max_count: None,
         }
     }
 
@@ -88,13 +125,110 @@ impl ArgumentDefinition {
             required: false,
             arity: ArgumentArity::Remainder,
             default: Some(default.into_iter().map(Into::into).collect()),
+            value_type: ArgumentType::Text,
+            choices: None,
+            min_count: 0,
+            max_count: None,
+        }
+    }
+
+    pub fn flag(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            required: false,
+            arity: ArgumentArity::Flag,
+            default: None,
+            value_type: ArgumentType::Text,
+            choices: None,
+            min_count: 0,
+            max_count: None,
+        }
+    }
+
+    pub fn option(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            required: false,
+            arity: ArgumentArity::Named,
+            default: None,
+            value_type: ArgumentType::Text,
+            choices: None,
+            min_count: 0,
+            max_count: None,
+        }
+    }
+
+    /// Bounds how many values a remainder argument accepts. `max` of `None` leaves
+    /// the upper end open.
+    pub fn with_count(mut self, min: usize, max: Option<usize>) -> Self {
+        self.min_count = min;
+        self.max_count = max;
+        self
+    }
+
+    /// Whether the argument is matched by position rather than by `--name`.
+    pub fn is_positional(&self) -> bool {
+        matches!(self.arity, ArgumentArity::Single | ArgumentArity::Remainder)
+    }
+
+    /// Restricts the argument to values of `value_type`.
+    pub fn with_type(mut self, value_type: ArgumentType) -> Self {
+        self.value_type = value_type;
+        self
+    }
+
+    /// Restricts the argument to one of `choices`.
+    pub fn with_choices(mut self, choices: &[&'static str]) -> Self {
+        self.choices = Some(choices.to_vec());
+        self
+    }
+
+    /// Checks that `value` is acceptable for this argument, returning it with any
+    /// keyword normalized to the spelling given in `choices`.
+    fn validate(&self, value: &str) -> Result<String, String> {
+        if let Some(choices) = &self.choices {
+            return choices
+                .iter()
+                .find(|choice| choice.eq_ignore_ascii_case(value))
+                .map(|choice| choice.to_string())
+                .ok_or_else(|| {
+                    format!(
+                        "Argument '{}' must be one of {}, got '{}'",
+                        self.name,
+                        choices.join(", "),
+                        value
+                    )
+                });
+        }
+
+        match self.value_type {
+            ArgumentType::Text => Ok(value.to_string()),
+            ArgumentType::Integer => {
+                value
+                    .parse::<i64>()
+                    .map(|_| value.to_string())
+                    .map_err(|_| {
+                        format!(
+                            "Argument '{}' must be an integer, got '{}'",
+                            self.name, value
+                        )
+                    })
+            }
         }
     }
 
     pub fn usage_token(&self) -> String {
+        let name = match &self.choices {
+            Some(choices) => choices.join("|"),
+            None => self.name.to_string(),
+        };
         let base = match self.arity {
-            ArgumentArity::Single => self.name.to_string(),
-            ArgumentArity::Remainder => format!("{}...", self.name),
+            ArgumentArity::Single => name,
+            ArgumentArity::Remainder => format!("{}...", name),
+            ArgumentArity::Flag => format!("--{}", self.name),
+            ArgumentArity::Named => format!("--{} {}", self.name, name),
         };
 
         let token = if self.required {
@@ -103,12 +237,11 @@ impl ArgumentDefinition {
             format!("[{}]", base)
         };
 
-        if matches!(self.arity, ArgumentArity::Single) {
-            if let Some(default) = &self.default {
-                if let Some(value) = default.first() {
-                    return format!("{}={}", token, value);
-                }
-            }
+        if matches!(self.arity, ArgumentArity::Single | ArgumentArity::Named)
+            && let Some(default) = &self.default
+            && let Some(value) = default.first()
+        {
+            return format!("{}={}", token, value);
         }
 
         token
@@ -123,6 +256,12 @@ impl ArgumentDefinition {
         let arity = match self.arity {
             ArgumentArity::Single => "single",
             ArgumentArity::Remainder => "variadic",
+            ArgumentArity::Flag => "flag",
+            ArgumentArity::Named => "named",
+        };
+        let arity = match self.value_type {
+            ArgumentType::Text => arity.to_string(),
+            ArgumentType::Integer => format!("{} integer", arity),
         };
 
         let mut summary = format!(
@@ -130,16 +269,16 @@ impl ArgumentDefinition {
             self.name, requirement, arity, self.description
         );
 
-        if let Some(default) = &self.default {
-            if !default.is_empty() {
-                let rendered = if matches!(self.arity, ArgumentArity::Single) {
-                    default.first().cloned().unwrap_or_default()
-                } else {
-                    default.join(" ")
-                };
+        if let Some(default) = &self.default
+            && !default.is_empty()
+        {
+            let rendered = if matches!(self.arity, ArgumentArity::Single | ArgumentArity::Named) {
+                default.first().cloned().unwrap_or_default()
+            } else {
+                default.join(" ")
+            };
 
-                summary.push_str(&format!(" [default: {}]", rendered));
-            }
+            summary.push_str(&format!(" [default: {}]", rendered));
         }
 
         summary
@@ -164,6 +303,27 @@ impl ArgumentParser {
         ArgumentParserBuilder::new(command_name)
     }
 
+    /// The fewest and most arguments the command accepts, not counting its name.
+    /// The most is `None` when a remainder argument takes any number of values.
+    pub fn arity(&self) -> (usize, Option<usize>) {
+        let mut min = 0;
+        let mut max = Some(0);
+        for spec in &self.specs {
+            let (least, most) = match spec.arity {
+                ArgumentArity::Single => (usize::from(spec.required), Some(1)),
+                ArgumentArity::Remainder if spec.required => {
+                    (spec.min_count.max(1), spec.max_count)
+                }
+                ArgumentArity::Remainder => (0, spec.max_count),
+                ArgumentArity::Flag => (0, Some(1)),
+                ArgumentArity::Named => (0, Some(2)),
+            };
+            min += least;
+            max = max.zip(most).map(|(max, most)| max + most);
+        }
+        (min, max)
+    }
+
     pub fn usage(&self) -> String {
         let tokens: Vec<String> = self
             .specs
@@ -191,22 +351,68 @@ impl ArgumentParser {
         sections.join("\n")
     }
 
-    fn error(&self, message: impl Into<String>) -> ArgumentError {
+    pub fn error(&self, message: impl Into<String>) -> ArgumentError {
         ArgumentError::new(self.command_name, message.into(), self.usage_with_details())
     }
 
     pub fn parse(&self, args: &[&str]) -> Result<ParsedArguments, ArgumentError> {
-        let raw: Vec<String> = args
-            .iter()
-            .map(|arg| arg.trim())
-            .filter(|arg| !arg.is_empty())
-            .map(|arg| arg.to_string())
-            .collect();
+        // Arguments arrive already split, so whitespace inside one is meaningful
+        let raw: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
 
-        let mut queue: VecDeque<String> = VecDeque::from(raw.clone());
+        let mut queue: VecDeque<String> = VecDeque::new();
         let mut values: HashMap<&'static str, Vec<String>> = HashMap::new();
+        let mut flags: HashSet<&'static str> = HashSet::new();
         let mut missing: Vec<&'static str> = Vec::new();
 
+        // Pull out flags and options first. Only declared names are recognised. With a
+        // leading `--` they can appear anywhere; bare, Redis-style (`MATCH pattern`),
+        // only once the required positionals have been given, so a key or value can
+        // still be spelled like an option. `--` ends them so a literal `--json` can
+        // still be passed as a value.
+        let takes_named = self.specs.iter().any(|spec| !spec.is_positional());
+        let required_positionals = self
+            .specs
+            .iter()
+            .filter(|spec| spec.is_positional() && spec.required)
+            .count();
+        let mut args = raw.iter();
+        while let Some(arg) = args.next() {
+            if !takes_named {
+                queue.push_back(arg.clone());
+                continue;
+            }
+            if arg == "--" {
+                queue.extend(args.by_ref().cloned());
+                break;
+            }
+
+            let (name, bare) = match arg.strip_prefix("--") {
+                Some(name) => (name, false),
+                None => (arg.as_str(), true),
+            };
+            let spec = self.specs.iter().find(|spec| {
+                !spec.is_positional()
+                    && spec.name.eq_ignore_ascii_case(name)
+                    && (!bare || queue.len() >= required_positionals)
+            });
+            match spec {
+                None => queue.push_back(arg.clone()),
+                Some(spec) if spec.arity == ArgumentArity::Flag => {
+                    flags.insert(spec.name);
+                }
+                Some(spec) => {
+                    let value = args.next().ok_or_else(|| {
+                        self.error(format!("Option '--{}' needs a value", spec.name))
+                    })?;
+                    values.insert(spec.name, vec![value.clone()]);
+                }
+            }
+        }
+
+        let last_positional = self
+            .specs
+            .iter()
+            .rposition(ArgumentDefinition::is_positional);
         for (index, spec) in self.specs.iter().enumerate() {
             match spec.arity {
                 ArgumentArity::Single => {
@@ -222,7 +428,7 @@ impl ArgumentParser {
                     }
                 }
                 ArgumentArity::Remainder => {
-                    if index != self.specs.len() - 1 {
+                    if Some(index) != last_positional {
                         return Err(self.error(format!(
                             "Argument '{}' captures the remaining input and must be the final argument",
                             spec.name
@@ -243,6 +449,12 @@ impl ArgumentParser {
                         values.insert(spec.name, remainder);
                     }
                 }
+                ArgumentArity::Flag => {}
+                ArgumentArity::Named => {
+                    if !values.contains_key(spec.name) {
+                        values.insert(spec.name, spec.default.clone().unwrap_or_default());
+                    }
+                }
             }
         }
 
@@ -256,11 +468,42 @@ impl ArgumentParser {
             return Err(self.error(format!("Missing required argument(s): {}", formatted)));
         }
 
+        for spec in &self.specs {
+            let count = values.get(spec.name).map_or(0, Vec::len);
+            if count == 0 {
+                continue;
+            }
+            if count < spec.min_count {
+                return Err(self.error(format!(
+                    "Argument '{}' takes at least {} values, got {}",
+                    spec.name, spec.min_count, count
+                )));
+            }
+            if let Some(max) = spec.max_count
+                && count > max
+            {
+                return Err(self.error(format!(
+                    "Argument '{}' takes at most {} values, got {}",
+                    spec.name, max, count
+                )));
+            }
+        }
+
+        // Defaults are checked too, so a bad default shows up the first time it is used
+        for spec in &self.specs {
+            for value in values.get_mut(spec.name).into_iter().flatten() {
+                *value = spec
+                    .validate(value)
+                    .map_err(|message| self.error(message))?;
+            }
+        }
+
         Ok(ParsedArguments {
             command_name: self.command_name,
             raw,
             order: self.specs.iter().map(|spec| spec.name).collect(),
             values,
+            flags,
         })
     }
 }
@@ -305,6 +548,43 @@ impl ArgumentParserBuilder {
         ))
     }
 
+    pub fn required_integer(self, name: &'static str, description: &'static str) -> Self {
+        self.arg(ArgumentDefinition::required(name, description).with_type(ArgumentType::Integer))
+    }
+
+    pub fn optional_integer(self, name: &'static str, description: &'static str) -> Self {
+        self.arg(ArgumentDefinition::optional(name, description).with_type(ArgumentType::Integer))
+    }
+
+    /// Adds a required remainder taking between `min` and `max` values.
+    pub fn required_remainder_with_count(
+        self,
+        name: &'static str,
+        description: &'static str,
+        min: usize,
+        max: Option<usize>,
+    ) -> Self {
+        self.arg(ArgumentDefinition::required_remainder(name, description).with_count(min, max))
+    }
+
+    pub fn flag(self, name: &'static str, description: &'static str) -> Self {
+        self.arg(ArgumentDefinition::flag(name, description))
+    }
+
+    pub fn option(self, name: &'static str, description: &'static str) -> Self {
+        self.arg(ArgumentDefinition::option(name, description))
+    }
+
+    /// Adds an optional keyword argument that must be one of `choices`.
+    pub fn one_of(
+        self,
+        name: &'static str,
+        description: &'static str,
+        choices: &[&'static str],
+    ) -> Self {
+        self.arg(ArgumentDefinition::optional(name, description).with_choices(choices))
+    }
+
     pub fn required_remainder(self, name: &'static str, description: &'static str) -> Self {
         self.arg(ArgumentDefinition::required_remainder(name, description))
     }
@@ -341,6 +621,7 @@ pub struct ParsedArguments {
     raw: Vec<String>,
     order: Vec<&'static str>,
     values: HashMap<&'static str, Vec<String>>,
+    flags: HashSet<&'static str>,
 }
 
 impl ParsedArguments {
@@ -356,6 +637,26 @@ impl ParsedArguments {
         &self.order
     }
 
+    /// Number of declared arguments, whether or not they were given.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Returns the value of the `index`th declared argument, the same as calling
+    /// `get` with its name.
+    pub fn get_index(&self, index: usize) -> Option<&str> {
+        self.order.get(index).and_then(|name| self.get(name))
+    }
+
+    /// Whether the `--name` flag was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
     pub fn has(&self, name: &str) -> bool {
         self.values
             .get(name)
@@ -370,6 +671,12 @@ impl ParsedArguments {
             .map(|value| value.as_str())
     }
 
+    /// Returns an integer argument. Arguments declared as `ArgumentType::Integer` have
+    /// already been validated, so this is only `None` when the argument is absent.
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.get(name).and_then(|value| value.parse().ok())
+    }
+
     pub fn get_or<'a>(&'a self, name: &str, fallback: &'a str) -> &'a str {
         self.get(name).unwrap_or(fallback)
     }
@@ -398,6 +705,7 @@ impl ParsedArguments {
         let mut lines = vec![format!("{} arguments:", self.command_name)];
         for name in &self.order {
             let rendered = match self.values.get(name) {
+                _ if self.flags.contains(name) => "set".to_string(),
                 Some(values) if !values.is_empty() => values.join(" "),
                 _ => "<none>".to_string(),
             };
@@ -459,4 +767,143 @@ impl From<ArgumentError> for io::Error {
     fn from(err: ArgumentError) -> Self {
         io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_arguments_are_checked() {
+        let parser = ArgumentParser::builder("INCRBY")
+            .required("key", "Key")
+            .required_integer("amount", "Amount")
+            .build();
+
+        let parsed = parser.parse(&["k", "-42"]).unwrap();
+        assert_eq!(parsed.get_i64("amount"), Some(-42));
+        assert_eq!(parsed.get("amount"), Some("-42"));
+        assert_eq!(parsed.get_i64("missing"), None);
+
+        for bad in ["ten", "1.5", "", "99999999999999999999"] {
+            let err = parser.parse(&["k", bad]).unwrap_err();
+            assert!(err.message().contains("must be an integer"), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn optional_integers_may_be_left_out() {
+        let parser = ArgumentParser::builder("LPOP")
+            .required("key", "Key")
+            .optional_integer("count", "Count")
+            .build();
+
+        assert_eq!(parser.parse(&["k"]).unwrap().get_i64("count"), None);
+        assert_eq!(parser.parse(&["k", "3"]).unwrap().get_i64("count"), Some(3));
+        assert!(parser.parse(&["k", "x"]).is_err());
+    }
+
+    #[test]
+    fn choices_accept_listed_keywords_in_any_case() {
+        let parser = ArgumentParser::builder("CLIENT")
+            .arg(
+                ArgumentDefinition::required("subcommand", "What to do")
+                    .with_choices(&["LIST", "KILL"]),
+            )
+            .build();
+
+        assert_eq!(
+            parser.parse(&["kill"]).unwrap().get("subcommand"),
+            Some("KILL")
+        );
+        let err = parser.parse(&["pause"]).unwrap_err();
+        assert!(
+            err.message().contains("must be one of LIST, KILL"),
+            "{}",
+            err.message()
+        );
+        assert_eq!(parser.usage(), "Usage: CLIENT <LIST|KILL>");
+    }
+
+    #[test]
+    fn flags_and_options_are_found_anywhere() {
+        let parser = ArgumentParser::builder("SCAN")
+            .required("cursor", "Cursor")
+            .flag("verbose", "Verbose")
+            .option("match", "Pattern")
+            .build();
+
+        let parsed = parser.parse(&["--verbose", "0", "--match", "a*"]).unwrap();
+        assert!(parsed.flag("verbose"));
+        assert_eq!(parsed.get("match"), Some("a*"));
+        assert_eq!(parsed.get("cursor"), Some("0"));
+
+        let parsed = parser.parse(&["0"]).unwrap();
+        assert!(!parsed.flag("verbose"));
+        assert_eq!(parsed.get("match"), None);
+
+        // Redis-style bare names work once the positionals are given
+        assert_eq!(
+            parser.parse(&["0", "MATCH", "b*"]).unwrap().get("match"),
+            Some("b*")
+        );
+        assert!(parser.parse(&["0", "--match"]).is_err());
+        // After `--`, option names are ordinary values
+        let parser = ArgumentParser::builder("ECHO")
+            .required("text", "Text")
+            .flag("json", "Json")
+            .build();
+        let parsed = parser.parse(&["--", "--json"]).unwrap();
+        assert_eq!(parsed.get("text"), Some("--json"));
+        assert!(!parsed.flag("json"));
+    }
+
+    #[test]
+    fn remainder_counts_are_bounded() {
+        let parser = ArgumentParser::builder("PAIR")
+            .required_remainder_with_count("values", "Values", 2, Some(3))
+            .build();
+
+        assert!(
+            parser
+                .parse(&["a"])
+                .unwrap_err()
+                .message()
+                .contains("at least 2")
+        );
+        assert_eq!(
+            parser.parse(&["a", "b"]).unwrap().list("values"),
+            ["a", "b"]
+        );
+        assert_eq!(
+            parser.parse(&["a", "b", "c"]).unwrap().list("values").len(),
+            3
+        );
+        assert!(
+            parser
+                .parse(&["a", "b", "c", "d"])
+                .unwrap_err()
+                .message()
+                .contains("at most 3")
+        );
+        assert_eq!(parser.arity(), (2, Some(3)));
+    }
+
+    #[test]
+    fn arguments_can_be_read_by_position() {
+        let parser = ArgumentParser::builder("SETEX")
+            .required("key", "Key")
+            .required_integer("seconds", "Seconds")
+            .optional("value", "Value")
+            .build();
+
+        let parsed = parser.parse(&["k", "10"]).unwrap();
+        assert_eq!(parsed.len(), 3);
+        for (index, name) in parsed.names().iter().enumerate() {
+            assert_eq!(parsed.get_index(index), parsed.get(name));
+        }
+        assert_eq!(parsed.get_index(0), Some("k"));
+        assert_eq!(parsed.get_index(2), None);
+        assert_eq!(parsed.get_index(3), None);
+    }
+}
@@ -0,0 +1,168 @@
+use std::io;
+
+use crate::server::resp::RespValue;
+
+// Codes a failure's message may already start with, like Redis's error prefixes
+const ERROR_CODES: &[&str] = &[
+    "ERR",
+    "WRONGTYPE",
+    "NOAUTH",
+    "WRONGPASS",
+    "EXECABORT",
+    "OOM",
+    "NOPROTO",
+];
+
+/// What a command replies with, kept typed until it is written to the client so
+/// each protocol can show it in its own way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// A status such as `OK`
+    Simple(String),
+    /// A failure, starting with an error code like `ERR`
+    Error(String),
+    Integer(i64),
+    /// `None` is nil
+    Bulk(Option<String>),
+    Array(Vec<Reply>),
+}
+
+impl Reply {
+    pub fn ok() -> Self {
+        Reply::Simple("OK".to_string())
+    }
+
+    pub fn nil() -> Self {
+        Reply::Bulk(None)
+    }
+
+    pub fn bulk(value: impl Into<String>) -> Self {
+        Reply::Bulk(Some(value.into()))
+    }
+
+    /// An array of bulk strings, one per item.
+    pub fn list<S: Into<String>>(items: impl IntoIterator<Item = S>) -> Self {
+        Reply::Array(items.into_iter().map(Reply::bulk).collect())
+    }
+
+    /// How a failed command is described to the client, in either protocol: one line
+    /// starting with an error code, `ERR` unless the message names a more specific
+    /// one. Usage text after the first line is dropped.
+    pub fn from_error(e: &io::Error) -> Self {
+        let message = e.to_string();
+        let line = message.lines().next().unwrap_or_default();
+        let code = line.split(' ').next().unwrap_or_default();
+        if ERROR_CODES.contains(&code) {
+            Reply::Error(line.to_string())
+        } else {
+            Reply::Error(format!("ERR {}", line))
+        }
+    }
+
+    /// The reply as the inline protocol shows it: one value per line, with nil as
+    /// `(nil)` and errors behind the same leading `-` as RESP, so they can't be
+    /// mistaken for a value.
+    pub fn render(&self) -> String {
+        match self {
+            Reply::Simple(value) | Reply::Bulk(Some(value)) => value.clone(),
+            Reply::Error(message) => format!("-{}", message),
+            Reply::Integer(value) => value.to_string(),
+            Reply::Bulk(None) => "(nil)".to_string(),
+            Reply::Array(items) if items.is_empty() => "(empty array)".to_string(),
+            Reply::Array(items) => items
+                .iter()
+                .map(Reply::render)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    pub fn to_resp(&self) -> RespValue {
+        match self {
+            Reply::Simple(value) => RespValue::SimpleString(value.clone()),
+            Reply::Error(message) => RespValue::Error(message.clone()),
+            Reply::Integer(value) => RespValue::Integer(*value),
+            Reply::Bulk(value) => {
+                RespValue::BulkString(value.as_ref().map(|value| value.as_bytes().to_vec()))
+            }
+            Reply::Array(items) => {
+                RespValue::Array(Some(items.iter().map(Reply::to_resp).collect()))
+            }
+        }
+    }
+}
+
+impl From<i64> for Reply {
+    fn from(value: i64) -> Self {
+        Reply::Integer(value)
+    }
+}
+
+impl From<usize> for Reply {
+    fn from(value: usize) -> Self {
+        Reply::Integer(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+/// Yes/no answers are the integers 1 and 0, as in Redis.
+impl From<bool> for Reply {
+    fn from(value: bool) -> Self {
+        Reply::Integer(i64::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(value: &str) -> RespValue {
+        RespValue::BulkString(Some(value.as_bytes().to_vec()))
+    }
+
+    #[test]
+    fn every_variant_renders_for_inline_clients() {
+        assert_eq!(Reply::ok().render(), "OK");
+        assert_eq!(Reply::Error("ERR bad".to_string()).render(), "-ERR bad");
+        assert_eq!(Reply::Integer(-3).render(), "-3");
+        assert_eq!(Reply::bulk("value").render(), "value");
+        assert_eq!(Reply::nil().render(), "(nil)");
+        assert_eq!(Reply::Array(Vec::new()).render(), "(empty array)");
+        assert_eq!(
+            Reply::Array(vec![Reply::bulk("a"), Reply::nil(), Reply::Integer(1)]).render(),
+            "a\n(nil)\n1"
+        );
+    }
+
+    #[test]
+    fn every_variant_converts_to_resp() {
+        assert_eq!(
+            Reply::ok().to_resp(),
+            RespValue::SimpleString("OK".to_string())
+        );
+        assert_eq!(
+            Reply::Error("ERR bad".to_string()).to_resp(),
+            RespValue::Error("ERR bad".to_string())
+        );
+        assert_eq!(Reply::Integer(-3).to_resp(), RespValue::Integer(-3));
+        assert_eq!(Reply::bulk("value").to_resp(), bulk("value"));
+        assert_eq!(Reply::nil().to_resp(), RespValue::BulkString(None));
+        assert_eq!(
+            Reply::Array(vec![Reply::list(["a"]), Reply::nil()]).to_resp(),
+            RespValue::Array(Some(vec![
+                RespValue::Array(Some(vec![bulk("a")])),
+                RespValue::BulkString(None)
+            ]))
+        );
+        assert_eq!(Reply::Integer(1).to_resp().encode(), b":1\r\n");
+    }
+
+    #[test]
+    fn errors_keep_a_known_code_or_get_err() {
+        let reply = Reply::from_error(&io::Error::other("WRONGTYPE wrong kind\nUsage: GET <key>"));
+        assert_eq!(reply, Reply::Error("WRONGTYPE wrong kind".to_string()));
+        let reply = Reply::from_error(&io::Error::other("something failed"));
+        assert_eq!(reply, Reply::Error("ERR something failed".to_string()));
+        assert_eq!(Reply::from(true), Reply::Integer(1));
+        assert_eq!(Reply::from(0usize), Reply::Integer(0));
+    }
+}
@@ -0,0 +1,142 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenizeError {
+    message: String,
+}
+
+impl TokenizeError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for TokenizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for TokenizeError {}
+
+impl From<TokenizeError> for io::Error {
+    fn from(err: TokenizeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
+    }
+}
+
+/// Splits an inline command into arguments.
+///
+/// Arguments are separated by whitespace. Double-quoted arguments may contain
+/// whitespace and the escapes `\n`, `\r`, `\t`, `\"` and `\\` (any other escaped
+/// character stands for itself). Single-quoted arguments are taken literally except
+/// for `\'`. A closing quote must be followed by whitespace or the end of input.
+pub fn tokenize(input: &str) -> Result<Vec<String>, TokenizeError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let Some(&first) = chars.peek() else {
+            return Ok(tokens);
+        };
+
+        let token = match first {
+            '"' | '\'' => {
+                chars.next();
+                let token = read_quoted(&mut chars, first)?;
+                if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                    return Err(TokenizeError::new(
+                        "Closing quote must be followed by a space",
+                    ));
+                }
+                token
+            }
+            _ => {
+                let mut token = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    token.push(c);
+                }
+                token
+            }
+        };
+
+        tokens.push(token);
+    }
+}
+
+fn read_quoted(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    quote: char,
+) -> Result<String, TokenizeError> {
+    let mut token = String::new();
+
+    loop {
+        match chars.next() {
+            None => return Err(TokenizeError::new("Unterminated quoted argument")),
+            Some(c) if c == quote => return Ok(token),
+            Some('\\') if quote == '"' => match chars.next() {
+                Some('n') => token.push('\n'),
+                Some('r') => token.push('\r'),
+                Some('t') => token.push('\t'),
+                Some(other) => token.push(other),
+                None => return Err(TokenizeError::new("Unterminated quoted argument")),
+            },
+            Some('\\') if chars.peek() == Some(&'\'') => {
+                chars.next();
+                token.push('\'');
+            }
+            Some(c) => token.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(input: &str) -> Vec<String> {
+        tokenize(input).unwrap()
+    }
+
+    #[test]
+    fn whitespace_separates_arguments() {
+        assert_eq!(tokens("SET  key\tvalue "), ["SET", "key", "value"]);
+        assert!(tokens("   ").is_empty());
+    }
+
+    #[test]
+    fn quotes_keep_phrases_together() {
+        assert_eq!(tokens("SET k \"hello world\""), ["SET", "k", "hello world"]);
+        assert_eq!(tokens("SET k 'it is'"), ["SET", "k", "it is"]);
+        assert_eq!(tokens("SET k \"\""), ["SET", "k", ""]);
+    }
+
+    #[test]
+    fn escapes_in_double_quotes() {
+        assert_eq!(tokens(r#""a\nb\tc\"d\\e\x""#), ["a\nb\tc\"d\\ex"]);
+        // Single quotes only unescape the quote itself
+        assert_eq!(tokens(r"'it\'s \n'"), ["it's \\n"]);
+    }
+
+    #[test]
+    fn unterminated_quotes_are_errors() {
+        assert!(tokenize("SET k \"open").is_err());
+        assert!(tokenize("SET k 'open").is_err());
+        assert!(tokenize("SET k \"ends in escape\\").is_err());
+    }
+
+    #[test]
+    fn a_closing_quote_must_end_the_argument() {
+        assert!(tokenize("SET k \"a\"b").is_err());
+    }
+}
@@ -1,9 +1,448 @@
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs};
+
+use crate::log::Level;
+use crate::store::{self, EvictionPolicy};
+
+/// When the append-only file is flushed to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    // After every write: slowest, but an acknowledged write is never lost
+    Always,
+    // At most once a second: a crash loses about a second of writes
+    EverySec,
+    // Leave it to the operating system
+    No,
+}
+
+impl FsyncPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FsyncPolicy::Always => "always",
+            FsyncPolicy::EverySec => "everysec",
+            FsyncPolicy::No => "no",
+        }
+    }
+}
+
+impl fmt::Display for FsyncPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FsyncPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "always" => Ok(FsyncPolicy::Always),
+            "everysec" => Ok(FsyncPolicy::EverySec),
+            "no" => Ok(FsyncPolicy::No),
+            _ => Err(format!("unknown fsync policy '{}'", s)),
+        }
+    }
+}
+
+/// Settings the server starts with.
+///
+/// Each field can be set by name from a config file or an environment variable;
+/// see `get_config` for where those come from and which wins.
+#[derive(Debug, Clone)]
 pub struct Config {
-    pub port: i32,
+    // IPv4 or IPv6 addresses of the interfaces to listen on, each on every port
+    pub bind: Vec<String>,
+    // 0 asks the operating system for any free port, which is handy for tests
+    pub port: u16,
+    // Number of threads serving client connections. Each connection holds one until
+    // it closes; connections beyond this many wait for a worker to free up.
+    pub workers: usize,
+    // Connections beyond this many, waiting ones included, are turned away with an error
+    pub maxclients: usize,
+    // Close connections that send nothing for this long, None to never time out
+    pub idle_timeout: Option<Duration>,
+    // Background check closing connections whose last command was this long ago,
+    // including subscribers, None to disable
+    pub reap_idle_after: Option<Duration>,
+    // Number of logical databases clients can SELECT between
+    pub databases: usize,
+    // Independently locked parts each database's keys are split between
+    pub shards: usize,
+    // Most keys each database may hold across all of its shards, None for no limit
+    pub maxkeys: Option<usize>,
+    // How a full database makes room for a new key
+    pub eviction_policy: EvictionPolicy,
+    // Password clients must AUTH with before running commands, None to allow anyone
+    pub requirepass: Option<String>,
+    // Where SAVE writes snapshots and startup loads them from, None to disable
+    pub snapshot_path: Option<PathBuf>,
+    // Log every write to this file and replay it on startup, None to disable
+    pub appendonly: Option<PathBuf>,
+    pub appendfsync: FsyncPolicy,
+    // Most verbose log messages still written
+    pub log_level: Level,
+    // Allow DEBUG, which can stall the server and is only meant for testing
+    pub enable_debug_command: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind: vec!["127.0.0.1".to_string()],
+            port: 5215,
+            workers: 16,
+            maxclients: 10000,
+            idle_timeout: Some(Duration::from_secs(300)),
+            reap_idle_after: None,
+            databases: 16,
+            shards: store::DEFAULT_SHARDS,
+            maxkeys: None,
+            eviction_policy: EvictionPolicy::NoEviction,
+            requirepass: None,
+            snapshot_path: Some(PathBuf::from("dump.rdb")),
+            appendonly: None,
+            appendfsync: FsyncPolicy::EverySec,
+            log_level: Level::Info,
+            enable_debug_command: false,
+        }
+    }
+}
+
+/// A configuration value that can't be used, described well enough to fix it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError(String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration: {}", self.0)
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Every setting that can be given in a config file or the environment.
+pub const KEYS: &[&str] = &[
+    "bind",
+    "port",
+    "workers",
+    "maxclients",
+    "idle_timeout",
+    "reap_idle_after",
+    "databases",
+    "shards",
+    "maxkeys",
+    "eviction_policy",
+    "requirepass",
+    "snapshot_path",
+    "appendonly",
+    "appendfsync",
+    "log_level",
+    "enable_debug_command",
+];
+
+/// Settings CONFIG SET may change while the server runs. The rest are only read at
+/// startup, so changing them later would have no effect.
+pub const RUNTIME_KEYS: &[&str] = &["maxclients", "log_level"];
+
+/// Config file read when `RUSTDES_CONFIG` doesn't name one, if it exists.
+pub const DEFAULT_FILE: &str = "rustdes.conf";
+
+// Prefix of the environment variables overriding settings, e.g. RUSTDES_PORT
+const ENV_PREFIX: &str = "RUSTDES_";
+
+impl Config {
+    /// Sets the setting called `key` from its text form, as written in a config file.
+    ///
+    /// Durations are whole seconds, and optional settings are turned off with an
+    /// empty value (or 0 for `idle_timeout`, `reap_idle_after` and `maxkeys`).
+    /// `bind` takes a comma-separated list of addresses.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let value = value.trim();
+        match key.to_ascii_lowercase().as_str() {
+            "bind" => {
+                self.bind = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|address| !address.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                if self.bind.is_empty() {
+                    return Err(ConfigError("bind needs at least one address".to_string()));
+                }
+            }
+            "port" => self.port = parse_port(value)?,
+            "workers" => self.workers = parse_count(key, value)?,
+            "maxclients" => self.maxclients = parse_count(key, value)?,
+            "idle_timeout" => self.idle_timeout = parse_seconds(key, value)?,
+            "reap_idle_after" => self.reap_idle_after = parse_seconds(key, value)?,
+            "databases" => self.databases = parse_count(key, value)?,
+            "shards" => self.shards = parse_count(key, value)?,
+            "maxkeys" => {
+                self.maxkeys = parse_number(key, value).map(|n| Some(n).filter(|n| *n > 0))?
+            }
+            "eviction_policy" => self.eviction_policy = value.parse().map_err(ConfigError)?,
+            "requirepass" => self.requirepass = non_empty(value).map(str::to_string),
+            "snapshot_path" => self.snapshot_path = non_empty(value).map(PathBuf::from),
+            "appendonly" => self.appendonly = non_empty(value).map(PathBuf::from),
+            "appendfsync" => self.appendfsync = value.parse().map_err(ConfigError)?,
+            "log_level" => self.log_level = value.parse().map_err(ConfigError)?,
+            "enable_debug_command" => self.enable_debug_command = parse_bool(key, value)?,
+            _ => return Err(ConfigError(format!("unknown setting '{}'", key))),
+        }
+        Ok(())
+    }
+
+    /// The setting called `key` in the text form `set` accepts, or None if there is
+    /// no such setting.
+    pub fn get(&self, key: &str) -> Option<String> {
+        fn path(path: &Option<PathBuf>) -> String {
+            path.as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default()
+        }
+        fn seconds(duration: Option<Duration>) -> String {
+            duration
+                .map_or(0, |duration| duration.as_secs())
+                .to_string()
+        }
+
+        let value = match key.to_ascii_lowercase().as_str() {
+            "bind" => self.bind.join(","),
+            "port" => self.port.to_string(),
+            "workers" => self.workers.to_string(),
+            "maxclients" => self.maxclients.to_string(),
+            "idle_timeout" => seconds(self.idle_timeout),
+            "reap_idle_after" => seconds(self.reap_idle_after),
+            "databases" => self.databases.to_string(),
+            "shards" => self.shards.to_string(),
+            "maxkeys" => self.maxkeys.unwrap_or(0).to_string(),
+            "eviction_policy" => self.eviction_policy.to_string(),
+            "requirepass" => self.requirepass.clone().unwrap_or_default(),
+            "snapshot_path" => path(&self.snapshot_path),
+            "appendonly" => path(&self.appendonly),
+            "appendfsync" => self.appendfsync.to_string(),
+            "log_level" => self.log_level.as_str().to_ascii_lowercase(),
+            "enable_debug_command" => if self.enable_debug_command {
+                "yes"
+            } else {
+                "no"
+            }
+            .to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Applies every `key = value` line of a config file's contents. Blank lines and
+    /// lines starting with `#` are skipped.
+    pub fn apply_file(&mut self, contents: &str) -> Result<(), ConfigError> {
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(ConfigError(format!(
+                    "line {}: expected 'key = value', got '{}'",
+                    number + 1,
+                    line
+                )));
+            };
+            self.set(key.trim(), value)
+                .map_err(|e| ConfigError(format!("line {}: {}", number + 1, e.0)))?;
+        }
+        Ok(())
+    }
+
+    /// Applies every `RUSTDES_<KEY>` variable in `vars`, e.g. `RUSTDES_MAXCLIENTS`.
+    pub fn apply_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<(), ConfigError> {
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let key = key.to_ascii_lowercase();
+            if !KEYS.contains(&key.as_str()) {
+                continue;
+            }
+            self.set(&key, &value)
+                .map_err(|e| ConfigError(format!("{}: {}", name, e.0)))?;
+        }
+        Ok(())
+    }
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    Some(value).filter(|value| !value.is_empty())
+}
+
+fn parse_number(key: &str, value: &str) -> Result<usize, ConfigError> {
+    value.parse().map_err(|_| {
+        ConfigError(format!(
+            "{} must be a non-negative whole number, got '{}'",
+            key, value
+        ))
+    })
+}
+
+// A number of things that must be at least one
+fn parse_count(key: &str, value: &str) -> Result<usize, ConfigError> {
+    match parse_number(key, value)? {
+        0 => Err(ConfigError(format!("{} must be at least 1", key))),
+        count => Ok(count),
+    }
 }
 
-const CONFIG: Config = Config { port: 5215 };
+// yes/no, also accepting true/false and 1/0
+fn parse_bool(key: &str, value: &str) -> Result<bool, ConfigError> {
+    match value.to_ascii_lowercase().as_str() {
+        "yes" | "true" | "1" => Ok(true),
+        "no" | "false" | "0" => Ok(false),
+        _ => Err(ConfigError(format!(
+            "{} must be yes or no, got '{}'",
+            key, value
+        ))),
+    }
+}
+
+// Whole seconds, with 0 or nothing meaning never
+fn parse_seconds(key: &str, value: &str) -> Result<Option<Duration>, ConfigError> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let seconds = parse_number(key, value)?;
+    Ok(Some(Duration::from_secs(seconds as u64)).filter(|duration| !duration.is_zero()))
+}
+
+/// Parses a port to listen on: 1-65535, or 0 for any free port.
+pub fn parse_port(value: &str) -> Result<u16, ConfigError> {
+    value.trim().parse::<u16>().map_err(|_| {
+        ConfigError(format!(
+            "port must be between 1 and 65535 (or 0 for any free port), got '{}'",
+            value
+        ))
+    })
+}
+
+/// The configuration to start with, checked before anything is bound.
+///
+/// Settings come from, in order of precedence:
+/// 1. `RUSTDES_<KEY>` environment variables, e.g. `RUSTDES_PORT=6380`
+/// 2. The config file named by `RUSTDES_CONFIG`, or `rustdes.conf` in the working
+///    directory if that exists. A file named explicitly must exist.
+/// 3. The defaults
+pub fn get_config() -> Result<Config, ConfigError> {
+    let mut config = Config::default();
+
+    let path = match env::var_os(format!("{}CONFIG", ENV_PREFIX)) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(PathBuf::from(DEFAULT_FILE)).filter(|path| path.exists()),
+    };
+    if let Some(path) = path {
+        load_file(&mut config, &path)?;
+    }
+
+    // env::vars would panic on a variable that isn't valid UTF-8, even an unrelated one
+    let vars = env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    config.apply_env(vars)?;
+    Ok(config)
+}
+
+fn load_file(config: &mut Config, path: &Path) -> Result<(), ConfigError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| ConfigError(format!("can't read {} -- {}", path.display(), e)))?;
+    config
+        .apply_file(&contents)
+        .map_err(|e| ConfigError(format!("{}, {}", path.display(), e.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ports_must_fit_in_sixteen_bits() {
+        assert_eq!(parse_port("6379"), Ok(6379));
+        assert_eq!(parse_port(" 65535 "), Ok(65535));
+        // 0 asks the system for any free port
+        assert_eq!(parse_port("0"), Ok(0));
+
+        for bad in ["65536", "-1", "port", ""] {
+            let err = parse_port(bad).unwrap_err();
+            assert!(err.to_string().contains("between 1 and 65535"), "{}", bad);
+        }
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn defaults_are_kept_until_overridden() {
+        let mut config = Config::default();
+        config.apply_file("").unwrap();
+        config.apply_env(vars(&[("HOME", "/root")])).unwrap();
+        let defaults = Config::default();
+        for key in KEYS {
+            assert_eq!(config.get(key), defaults.get(key), "{}", key);
+        }
+        assert_eq!(config.bind, ["127.0.0.1"]);
+        assert_eq!(config.get("maxclients").as_deref(), Some("10000"));
+    }
+
+    #[test]
+    fn the_file_overrides_defaults_and_the_environment_overrides_the_file() {
+        let mut config = Config::default();
+        config
+            .apply_file(
+                "# comment\n\nport = 6380\nbind = 0.0.0.0, ::1\nrequirepass = secret\nworkers=4\n",
+            )
+            .unwrap();
+        assert_eq!(config.port, 6380);
+        assert_eq!(config.bind, ["0.0.0.0", "::1"]);
+        assert_eq!(config.requirepass.as_deref(), Some("secret"));
+        assert_eq!(config.workers, 4);
+
+        config
+            .apply_env(vars(&[
+                ("RUSTDES_PORT", "6381"),
+                ("RUSTDES_MAXCLIENTS", "5"),
+                ("RUSTDES_CONFIG", "ignored.conf"),
+            ]))
+            .unwrap();
+        assert_eq!(config.port, 6381);
+        assert_eq!(config.maxclients, 5);
+        assert_eq!(config.workers, 4);
+    }
+
+    #[test]
+    fn malformed_settings_are_described() {
+        let err = Config::default()
+            .apply_file("port = 6380\nnonsense\n")
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+
+        let err = Config::default().apply_file("colour = blue").unwrap_err();
+        assert!(
+            err.to_string().contains("unknown setting 'colour'"),
+            "{}",
+            err
+        );
 
-pub fn get_config() -> Config {
-    return CONFIG;
+        let err = Config::default()
+            .apply_env(vars(&[("RUSTDES_WORKERS", "0")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("RUSTDES_WORKERS"), "{}", err);
+    }
 }
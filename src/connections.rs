@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::locks::MutexExt;
+use crate::{info, time};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Active,
+    Disconnected,
+} // Connection statuses
+
+impl ConnectionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionStatus::Active => "active",
+            ConnectionStatus::Disconnected => "disconnected",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConnectionInfo {
+    status: ConnectionStatus,
+    address: String,
+    endpoint: String,
+    connected_at: SystemTime,
+    last_activity: SystemTime,
+    // Second handle to the socket so the server can close it from outside the client thread
+    stream: TcpStream,
+}
+
+impl ConnectionInfo {
+    /// A connection from `address` that was just accepted on `endpoint`.
+    pub fn new(address: String, endpoint: String, stream: TcpStream) -> Self {
+        let now = SystemTime::now();
+        Self {
+            status: ConnectionStatus::Active,
+            address,
+            endpoint,
+            connected_at: now,
+            last_activity: now,
+            stream,
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn connected_at(&self) -> SystemTime {
+        self.connected_at
+    }
+}
+
+/// One connection as reported by CLIENT LIST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSummary {
+    pub id: u64,
+    pub address: String,
+    pub endpoint: String,
+    pub status: ConnectionStatus,
+    // Time since the connection was accepted
+    pub age: Duration,
+    // Time since the client last sent anything
+    pub idle: Duration,
+}
+
+/// Every open connection of a server, keyed by connection id and shared by all of
+/// its listeners and clients.
+#[derive(Debug, Default)]
+pub struct Connections {
+    // Mutex allows for safe mutation across threads
+    clients: Mutex<HashMap<u64, ConnectionInfo>>,
+}
+
+impl Connections {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new connection unless `limit` active ones are already open.
+    /// Counting and inserting under one lock keeps concurrent listeners from
+    /// admitting more than the limit between them.
+    pub fn admit(&self, id: u64, info: ConnectionInfo, limit: usize) -> bool {
+        let mut clients = self.clients.lock_unpoisoned();
+        let active = clients
+            .values()
+            .filter(|info| info.status == ConnectionStatus::Active)
+            .count();
+        if active >= limit {
+            return false;
+        }
+        clients.insert(id, info);
+        true
+    }
+
+    pub fn remove(&self, id: u64) -> Option<ConnectionInfo> {
+        self.clients.lock_unpoisoned().remove(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.lock_unpoisoned().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Update last activity
+    pub fn touch(&self, id: u64) {
+        let mut clients = self.clients.lock_unpoisoned();
+        if let Some(info) = clients.get_mut(&id) {
+            info.last_activity = SystemTime::now();
+        }
+    }
+
+    pub fn mark_disconnected(&self, id: u64) {
+        let mut clients = self.clients.lock_unpoisoned();
+        if let Some(info) = clients.get_mut(&id) {
+            info.status = ConnectionStatus::Disconnected;
+        }
+    }
+
+    /// Every connection as of one moment, ordered by id.
+    pub fn list(&self) -> Vec<ClientSummary> {
+        let now = SystemTime::now();
+        let clients = self.clients.lock_unpoisoned();
+        let mut summaries: Vec<ClientSummary> = clients
+            .iter()
+            .map(|(id, info)| ClientSummary {
+                id: *id,
+                address: info.address.clone(),
+                endpoint: info.endpoint.clone(),
+                status: info.status,
+                age: time::duration_between(info.connected_at, now),
+                idle: time::duration_between(info.last_activity, now),
+            })
+            .collect();
+        summaries.sort_by_key(|summary| summary.id);
+        summaries
+    }
+
+    /// Closes connection `id` from outside its thread, returning whether it was
+    /// open. Its client sees the end of input and cleans up as usual.
+    pub fn kill(&self, id: u64) -> bool {
+        let clients = self.clients.lock_unpoisoned();
+        match clients.get(&id) {
+            Some(info) => {
+                let _ = info.stream.shutdown(Shutdown::Both);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Closes every active connection that has sent nothing for longer than
+    /// `threshold`, returning their ids.
+    pub fn reap_idle(&self, threshold: Duration) -> Vec<u64> {
+        let mut clients = self.clients.lock_unpoisoned();
+        let mut reaped = Vec::new();
+        for (id, info) in clients.iter_mut() {
+            if info.status == ConnectionStatus::Active
+                && time::elapsed_since(info.last_activity) > threshold
+            {
+                info.status = ConnectionStatus::Disconnected;
+                let _ = info.stream.shutdown(Shutdown::Both);
+                reaped.push(*id);
+            }
+        }
+        reaped
+    }
+
+    /// Closes the read side of every connection. Clients blocked in read() wake up
+    /// and see the end of input, while one that is mid-command still gets to reply.
+    pub fn close_reads(&self) {
+        let clients = self.clients.lock_unpoisoned();
+        for info in clients.values() {
+            let _ = info.stream.shutdown(Shutdown::Read);
+        }
+    }
+}
+
+/// Starts a thread that closes connections idle for longer than `threshold`,
+/// checking every `interval`. It exits once the registry is dropped.
+pub fn spawn_idle_reaper(
+    connections: &Arc<Connections>,
+    threshold: Duration,
+    interval: Duration,
+) -> thread::JoinHandle<()> {
+    let connections: Weak<Connections> = Arc::downgrade(connections);
+
+    thread::spawn(move || {
+        loop {
+            thread::sleep(interval);
+
+            let Some(connections) = connections.upgrade() else {
+                break;
+            };
+            for id in connections.reap_idle(threshold) {
+                info!("Reaped connection {} after being idle", id);
+            }
+        }
+    })
+}
@@ -0,0 +1,193 @@
+/// Redis-style glob matching.
+///
+/// Supported syntax:
+/// - `*` matches any sequence of characters (including none)
+/// - `?` matches exactly one character
+/// - `[abc]`, `[a-z]` match one character from a set or range, `[^...]` negates it
+/// - `\x` matches `x` literally
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    match_from(&pattern, &text)
+}
+
+// Walks both strings once, remembering only the last `*` seen. A mismatch after it
+// retries with that `*` taking one more character; earlier stars never need
+// revisiting, since whatever a later one can't absorb they couldn't either.
+// That keeps the cost at most pattern length times text length.
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    let mut p = 0;
+    let mut t = 0;
+    // Pattern index just past the last `*`, and where in the text it stopped
+    let mut star: Option<(usize, usize)> = None;
+
+    loop {
+        if p < pattern.len() && pattern[p] == '*' {
+            while p < pattern.len() && pattern[p] == '*' {
+                p += 1;
+            }
+            if p == pattern.len() {
+                return true;
+            }
+            star = Some((p, t));
+            continue;
+        }
+
+        if p == pattern.len() && t == text.len() {
+            return true;
+        }
+        if p < pattern.len()
+            && t < text.len()
+            && let Some(next) = match_one(pattern, p, text[t])
+        {
+            p = next;
+            t += 1;
+            continue;
+        }
+
+        match star {
+            Some((after, stopped)) if stopped < text.len() => {
+                star = Some((after, stopped + 1));
+                p = after;
+                t = stopped + 1;
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Matches `c` against the single-character pattern element at `p`, which isn't a
+/// `*`. Returns the pattern index just past the element if it matched.
+fn match_one(pattern: &[char], p: usize, c: char) -> Option<usize> {
+    match pattern[p] {
+        '?' => Some(p + 1),
+        '[' => {
+            let (matched, next) = match_class(pattern, p + 1, c);
+            matched.then_some(next)
+        }
+        '\\' if p + 1 < pattern.len() => (pattern[p + 1] == c).then_some(p + 2),
+        literal => (literal == c).then_some(p + 1),
+    }
+}
+
+/// Matches `c` against the class starting at `start` (just past the `[`).
+/// Returns whether it matched and the pattern index just past the closing `]`.
+/// An unterminated class extends to the end of the pattern.
+fn match_class(pattern: &[char], start: usize, c: char) -> (bool, usize) {
+    let mut p = start;
+    let negate = p < pattern.len() && pattern[p] == '^';
+    if negate {
+        p += 1;
+    }
+
+    let mut matched = false;
+    while p < pattern.len() && pattern[p] != ']' {
+        if pattern[p] == '\\' && p + 1 < pattern.len() {
+            matched |= pattern[p + 1] == c;
+            p += 2;
+        } else if p + 2 < pattern.len() && pattern[p + 1] == '-' && pattern[p + 2] != ']' {
+            let (low, high) = if pattern[p] <= pattern[p + 2] {
+                (pattern[p], pattern[p + 2])
+            } else {
+                (pattern[p + 2], pattern[p])
+            };
+            matched |= low <= c && c <= high;
+            p += 3;
+        } else {
+            matched |= pattern[p] == c;
+            p += 1;
+        }
+    }
+
+    let next = if p < pattern.len() { p + 1 } else { p };
+    (matched != negate, next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn matches_like_redis() {
+        let cases = [
+            // Literals
+            ("", "", true),
+            ("", "a", false),
+            ("abc", "abc", true),
+            ("abc", "abd", false),
+            ("abc", "ab", false),
+            ("ab", "abc", false),
+            // Stars
+            ("*", "", true),
+            ("*", "anything", true),
+            ("**", "x", true),
+            ("a*", "a", true),
+            ("a*", "abc", true),
+            ("a*", "bac", false),
+            ("*c", "abc", true),
+            ("*c", "abd", false),
+            ("a*c", "ac", true),
+            ("a*c", "abbbc", true),
+            ("a*c", "abcd", false),
+            ("a*b*c", "axbyc", true),
+            ("a*b*c", "axcyb", false),
+            ("*ab*", "xxabyy", true),
+            ("*aab", "aaab", true),
+            ("*a*a*", "aa", true),
+            ("*a*a*", "ab", false),
+            ("h*llo", "heeeello", true),
+            // Question marks
+            ("?", "a", true),
+            ("?", "", false),
+            ("?", "ab", false),
+            ("h?llo", "hallo", true),
+            ("h?llo", "hllo", false),
+            ("*?", "", false),
+            ("*?", "a", true),
+            ("?*?", "ab", true),
+            // Classes
+            ("h[ae]llo", "hello", true),
+            ("h[ae]llo", "hallo", true),
+            ("h[ae]llo", "hillo", false),
+            ("h[^e]llo", "hallo", true),
+            ("h[^e]llo", "hello", false),
+            ("h[a-b]llo", "hbllo", true),
+            ("h[a-b]llo", "hcllo", false),
+            ("h[b-a]llo", "hallo", true),
+            ("[a-]", "-", true),
+            ("[]]", "]", false),
+            ("[\\]]", "]", true),
+            ("[abc", "b", true),
+            ("*[0-9]", "key7", true),
+            ("*[0-9]", "key", false),
+            // Escapes
+            ("\\*", "*", true),
+            ("\\*", "a", false),
+            ("a\\?", "a?", true),
+            ("a\\?", "ab", false),
+            ("\\", "\\", true),
+            // Multi-byte characters count as one
+            ("?", "é", true),
+            ("caf?", "café", true),
+            ("*é", "café", true),
+        ];
+
+        for (pattern, text, expected) in cases {
+            assert_eq!(
+                glob_match(pattern, text),
+                expected,
+                "{:?} against {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn many_stars_do_not_backtrack_exponentially() {
+        let pattern = "a*".repeat(30) + "b";
+        let text = "a".repeat(100);
+        assert!(!glob_match(&pattern, &text));
+        assert!(glob_match(&pattern, &(text + "b")));
+    }
+}
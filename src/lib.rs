@@ -0,0 +1,11 @@
+pub mod commands;
+pub mod config;
+pub mod connections;
+pub mod glob;
+pub mod locks;
+pub mod log;
+pub mod pubsub;
+pub mod server;
+pub mod stats;
+pub mod store;
+pub mod time;
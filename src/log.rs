@@ -0,0 +1,156 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How important a log message is. Messages less important than the configured
+/// level are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+}
+
+impl Level {
+    fn from_u8(value: u8) -> Level {
+        match value {
+            0 => Level::Error,
+            1 => Level::Warn,
+            2 => Level::Info,
+            _ => Level::Debug,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" | "warning" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            _ => Err(format!("unknown log level '{}'", s)),
+        }
+    }
+}
+
+// Most verbose level that still gets written
+static THRESHOLD: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+/// Sets the most verbose level that is still written.
+pub fn set_level(level: Level) {
+    THRESHOLD.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    Level::from_u8(THRESHOLD.load(Ordering::Relaxed))
+}
+
+pub fn enabled(level: Level) -> bool {
+    level <= self::level()
+}
+
+/// Writes `args` to stderr if `level` passes the configured threshold.
+pub fn log(level: Level, args: fmt::Arguments) {
+    // Checked up front so suppressed messages don't contend for the stderr lock
+    if !enabled(level) {
+        return;
+    }
+    write_record(&mut std::io::stderr().lock(), self::level(), level, args);
+}
+
+/// Writes one line for a message at `level` to `out`, unless it is more verbose
+/// than `threshold`. Failing to log is not worth failing the caller over.
+pub fn write_record(out: &mut impl Write, threshold: Level, level: Level, args: fmt::Arguments) {
+    if level > threshold {
+        return;
+    }
+    let _ = writeln!(out, "[{}] {}", level, args);
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::Level::Error, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::Level::Warn, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::Level::Info, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::Level::Debug, format_args!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(threshold: Level, level: Level, message: &str) -> String {
+        let mut out = Vec::new();
+        write_record(&mut out, threshold, level, format_args!("{}", message));
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn debug_messages_are_suppressed_at_info() {
+        assert_eq!(record(Level::Info, Level::Debug, "sent GET a"), "");
+        assert_eq!(
+            record(Level::Info, Level::Info, "connected"),
+            "[INFO] connected\n"
+        );
+        assert_eq!(
+            record(Level::Info, Level::Error, "failed"),
+            "[ERROR] failed\n"
+        );
+    }
+
+    #[test]
+    fn debug_messages_are_written_at_debug() {
+        assert_eq!(
+            record(Level::Debug, Level::Debug, "sent GET a"),
+            "[DEBUG] sent GET a\n"
+        );
+        assert_eq!(record(Level::Error, Level::Warn, "slow"), "");
+    }
+
+    #[test]
+    fn levels_parse_case_insensitively() {
+        assert_eq!("DEBUG".parse(), Ok(Level::Debug));
+        assert_eq!("warning".parse(), Ok(Level::Warn));
+        assert!("verbose".parse::<Level>().is_err());
+    }
+}
@@ -1,10 +1,15 @@
-mod commands;
-mod config;
-mod server;
-
-use server::server as web_server;
+use rustdes::config;
+use rustdes::server::server as web_server;
+use rustdes::{error, log};
 
 fn main() {
-    let cfg = config::get_config();
-    web_server::start_server(cfg.port);
+    let cfg = match config::get_config() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    log::set_level(cfg.log_level);
+    web_server::start_server(&[cfg.port], &cfg);
 }
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::mpsc::Sender;
+
+use crate::locks::MutexExt;
+
+/// A message published to a channel, on its way to one subscriber.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub channel: String,
+    pub payload: String,
+}
+
+/// Channels and the connections subscribed to them, shared by every connection of
+/// a server. Each subscriber is identified by its connection id and receives
+/// messages through its own queue, which its connection drains between commands.
+#[derive(Debug, Default)]
+pub struct PubSub {
+    channels: Mutex<HashMap<String, HashMap<u64, Sender<Message>>>>,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, channel: &str, subscriber: u64, sender: Sender<Message>) {
+        let mut channels = self.channels.lock_unpoisoned();
+        channels
+            .entry(channel.to_string())
+            .or_default()
+            .insert(subscriber, sender);
+    }
+
+    /// Drops `subscriber` from every channel, e.g. once its connection closes.
+    pub fn unsubscribe_all(&self, subscriber: u64) {
+        let mut channels = self.channels.lock_unpoisoned();
+        for subscribers in channels.values_mut() {
+            subscribers.remove(&subscriber);
+        }
+        channels.retain(|_, subscribers| !subscribers.is_empty());
+    }
+
+    /// Sends `payload` to everyone subscribed to `channel`, returning how many
+    /// received it. Subscribers whose connection has gone away are dropped.
+    pub fn publish(&self, channel: &str, payload: &str) -> usize {
+        let mut channels = self.channels.lock_unpoisoned();
+        let Some(subscribers) = channels.get_mut(channel) else {
+            return 0;
+        };
+
+        subscribers.retain(|_, sender| {
+            sender
+                .send(Message {
+                    channel: channel.to_string(),
+                    payload: payload.to_string(),
+                })
+                .is_ok()
+        });
+
+        let delivered = subscribers.len();
+        if delivered == 0 {
+            channels.remove(channel);
+        }
+        delivered
+    }
+}
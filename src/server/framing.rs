@@ -0,0 +1,124 @@
+use std::io::{self, Read};
+
+use crate::server::resp::{CommandDecoder, RespError};
+
+// Upper bound on a single command so a client can't grow the buffer without limit
+pub const MAX_FRAME_LEN: usize = 512 * 1024 * 1024;
+
+const READ_CHUNK: usize = 4096;
+
+/// One complete command read from a client, in the protocol it was sent with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// A newline-terminated plain text command, without its `\n` or `\r\n` ending
+    Inline(Vec<u8>),
+    /// A RESP array of bulk strings
+    Resp(Vec<Vec<u8>>),
+}
+
+/// Splits a byte stream into commands.
+///
+/// Bytes are accumulated until a full command is available, so commands longer than
+/// a single read, or split across TCP segments, arrive whole. A command starting with
+/// `*` is parsed as RESP; anything else is a newline-terminated inline command, with
+/// either Unix or Windows line endings.
+///
+/// Pipelined commands (several sent in one write) stay in the buffer and are returned
+/// one per call, in the order they were sent.
+pub struct FrameReader<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    // How much of `buffer` is known not to contain a newline
+    scanned: usize,
+    // Where a partly received RESP command has got to
+    decoder: CommandDecoder,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            scanned: 0,
+            decoder: CommandDecoder::default(),
+        }
+    }
+
+    /// The underlying reader, e.g. to adjust socket options.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the next command if it has already been read in full, without
+    /// waiting for more input.
+    pub fn buffered_frame(&mut self) -> io::Result<Option<Frame>> {
+        Ok(self.take_frame()?)
+    }
+
+    /// Returns the next command, or `None` once the peer has closed the connection.
+    /// Unterminated inline bytes left at end of input are returned as a final command.
+    pub fn next_frame(&mut self) -> io::Result<Option<Frame>> {
+        loop {
+            if let Some(frame) = self.take_frame()? {
+                return Ok(Some(frame));
+            }
+
+            if self.buffer.len() > MAX_FRAME_LEN {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("command exceeds {} bytes", MAX_FRAME_LEN),
+                ));
+            }
+
+            let mut chunk = [0u8; READ_CHUNK];
+            let n = self.reader.read(&mut chunk)?;
+            if n == 0 {
+                return Ok(self.take_remainder());
+            }
+
+            self.buffer.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// Removes the first complete command from the buffer, if there is one.
+    fn take_frame(&mut self) -> Result<Option<Frame>, RespError> {
+        if self.buffer.first() == Some(&b'*') {
+            let Some((args, used)) = self.decoder.decode(&self.buffer)? else {
+                return Ok(None);
+            };
+            self.buffer.drain(..used);
+            return Ok(Some(Frame::Resp(args)));
+        }
+
+        match self.buffer[self.scanned..].iter().position(|b| *b == b'\n') {
+            Some(offset) => {
+                let end = self.scanned + offset;
+                let frame = strip_cr(&self.buffer[..end]).to_vec();
+                self.buffer.drain(..=end);
+                self.scanned = 0;
+                Ok(Some(Frame::Inline(frame)))
+            }
+            None => {
+                self.scanned = self.buffer.len();
+                Ok(None)
+            }
+        }
+    }
+
+    // A half-sent RESP command can't be run, but trailing inline text can
+    fn take_remainder(&mut self) -> Option<Frame> {
+        self.scanned = 0;
+        self.decoder = CommandDecoder::default();
+        let rest = std::mem::take(&mut self.buffer);
+        if rest.is_empty() || rest[0] == b'*' {
+            None
+        } else {
+            Some(Frame::Inline(strip_cr(&rest).to_vec()))
+        }
+    }
+}
+
+// Drops the `\r` of a CRLF line ending, so it can't end up in the last argument
+fn strip_cr(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
@@ -1,2 +1,6 @@
+mod framing;
+mod pool;
+pub mod resp;
+#[allow(clippy::module_inception)]
 pub mod server;
 mod util;
@@ -0,0 +1,78 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::locks::MutexExt;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Fixed-size pool of worker threads pulling jobs off a shared queue.
+///
+/// Jobs submitted while every worker is busy wait in the queue until one frees up.
+/// Dropping the pool closes the queue, lets the workers drain what is left, and
+/// joins them.
+pub struct ThreadPool {
+    workers: Vec<JoinHandle<()>>,
+    sender: Option<Sender<Job>>,
+}
+
+impl ThreadPool {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "a thread pool needs at least one worker");
+
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..size)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || worker_loop(&receiver))
+            })
+            .collect();
+
+        Self {
+            workers,
+            sender: Some(sender),
+        }
+    }
+
+    pub fn execute<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if let Some(sender) = &self.sender
+            && sender.send(Box::new(job)).is_err()
+        {
+            crate::warn!("Thread pool is shut down, dropping job");
+        }
+    }
+}
+
+fn worker_loop(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // Only hold the lock while waiting for a job, not while running it
+        let job = receiver.lock_unpoisoned().recv();
+        match job {
+            // A panicking job only loses itself; the worker carries on with the next
+            Ok(job) => {
+                if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                    crate::error!("A job panicked on a worker thread");
+                }
+            }
+            Err(_) => break, // Queue closed
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                crate::error!("A worker thread panicked");
+            }
+        }
+    }
+}
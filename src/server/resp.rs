@@ -0,0 +1,431 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+// Deepest nesting of arrays `decode` accepts before giving up
+const MAX_DEPTH: usize = 32;
+
+// Longest header line a request may have, so one that never ends isn't rescanned forever
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+/// What was decoded and how many bytes it used, or `None` if the input holds only
+/// part of it so far.
+type Decoded<T> = Result<Option<(T, usize)>, RespError>;
+
+/// A RESP2 value, as sent by clients like redis-cli and used for replies to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RespValue {
+    SimpleString(String),
+    Error(String),
+    Integer(i64),
+    /// `None` is the null bulk string (`$-1`)
+    BulkString(Option<Vec<u8>>),
+    /// `None` is the null array (`*-1`)
+    Array(Option<Vec<RespValue>>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RespError(String);
+
+impl fmt::Display for RespError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Protocol error: {}", self.0)
+    }
+}
+
+impl Error for RespError {}
+
+impl From<RespError> for io::Error {
+    fn from(err: RespError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+    }
+}
+
+impl RespValue {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(&mut out);
+        out
+    }
+
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        match self {
+            RespValue::SimpleString(value) => {
+                out.push(b'+');
+                out.extend_from_slice(single_line(value).as_bytes());
+            }
+            RespValue::Error(message) => {
+                out.push(b'-');
+                out.extend_from_slice(single_line(message).as_bytes());
+            }
+            RespValue::Integer(value) => {
+                out.extend_from_slice(format!(":{}", value).as_bytes());
+            }
+            RespValue::BulkString(None) => out.extend_from_slice(b"$-1"),
+            RespValue::BulkString(Some(bytes)) => {
+                out.extend_from_slice(format!("${}\r\n", bytes.len()).as_bytes());
+                out.extend_from_slice(bytes);
+            }
+            RespValue::Array(None) => out.extend_from_slice(b"*-1"),
+            RespValue::Array(Some(items)) => {
+                out.extend_from_slice(format!("*{}\r\n", items.len()).as_bytes());
+                for item in items {
+                    item.encode_into(out);
+                }
+                // Every element already ended its own line
+                return;
+            }
+        }
+        out.extend_from_slice(b"\r\n");
+    }
+
+    /// Decodes one value from the front of `input`.
+    ///
+    /// Returns the value and the number of bytes it used, or `None` if `input` holds
+    /// only part of a value so far. Arrays may nest at most `MAX_DEPTH` deep.
+    pub fn decode(input: &[u8]) -> Decoded<RespValue> {
+        decode_nested(input, 0)
+    }
+
+    /// Decodes a request from the front of `input`: a flat array of bulk strings.
+    ///
+    /// Returns the arguments and the number of bytes they used, or `None` if `input`
+    /// holds only part of the request so far. Anything else in the array, including
+    /// a nested array, is an error, so a request can never recurse.
+    ///
+    /// Use a `CommandDecoder` when the request arrives a piece at a time.
+    pub fn decode_command(input: &[u8]) -> Decoded<Vec<Vec<u8>>> {
+        CommandDecoder::default().decode(input)
+    }
+}
+
+/// Decodes a request as its bytes arrive, like `RespValue::decode_command` but
+/// without starting over on every call.
+///
+/// The arguments decoded so far, and the length of the one still arriving, are
+/// kept between calls, so each byte of a request is looked at about once however
+/// many reads it takes to come in.
+#[derive(Debug, Default)]
+pub struct CommandDecoder {
+    // Arguments still to come, once the array header has been read
+    remaining: Option<usize>,
+    args: Vec<Vec<u8>>,
+    // Payload length of the bulk string whose header has been read
+    pending: Option<usize>,
+    // Bytes of the input the state above accounts for
+    used: usize,
+}
+
+impl CommandDecoder {
+    /// Carries on decoding the request at the front of `input`, which must be the
+    /// input from the previous call with any new bytes appended.
+    ///
+    /// Returns the arguments and the number of bytes they used once the request is
+    /// complete, leaving the decoder ready for the next one. After an error the
+    /// decoder starts over as well.
+    pub fn decode(&mut self, input: &[u8]) -> Decoded<Vec<Vec<u8>>> {
+        let decoded = self.resume(input);
+        if !matches!(decoded, Ok(None)) {
+            *self = Self::default();
+        }
+        decoded
+    }
+
+    fn resume(&mut self, input: &[u8]) -> Decoded<Vec<Vec<u8>>> {
+        let not_a_command = || RespError("expected an array of bulk strings".to_string());
+
+        let remaining = match self.remaining {
+            Some(remaining) => remaining,
+            None => {
+                let Some((line, used)) = header_line(input)? else {
+                    return Ok(None);
+                };
+                let count = match line.split_first() {
+                    Some((b'*', count)) => parse_int(line_text(count)?)?,
+                    _ => return Err(not_a_command()),
+                };
+                if count < 0 {
+                    return Err(not_a_command());
+                }
+                self.used = used;
+                *self.remaining.insert(count as usize)
+            }
+        };
+
+        for _ in 0..remaining {
+            let len = match self.pending {
+                Some(len) => len,
+                None => {
+                    let Some((line, line_len)) = header_line(&input[self.used..])? else {
+                        return Ok(None);
+                    };
+                    let len = match line.split_first() {
+                        Some((b'$', len)) => parse_int(line_text(len)?)?,
+                        _ => return Err(not_a_command()),
+                    };
+                    if len < 0 {
+                        return Err(not_a_command());
+                    }
+                    self.used += line_len;
+                    *self.pending.insert(len as usize)
+                }
+            };
+
+            let Some(bytes) = bulk_payload(input, self.used, len)? else {
+                return Ok(None);
+            };
+            self.used += bytes.len() + 2;
+            self.args.push(bytes.to_vec());
+            self.pending = None;
+            if let Some(remaining) = &mut self.remaining {
+                *remaining -= 1;
+            }
+        }
+
+        Ok(Some((std::mem::take(&mut self.args), self.used)))
+    }
+}
+
+fn decode_nested(input: &[u8], depth: usize) -> Decoded<RespValue> {
+    let Some((line, mut used)) = read_line(input) else {
+        return Ok(None);
+    };
+    let Some((&kind, body)) = line.split_first() else {
+        return Err(RespError("empty type line".to_string()));
+    };
+    let text = line_text(body)?;
+
+    let value = match kind {
+        b'+' => RespValue::SimpleString(text.to_string()),
+        b'-' => RespValue::Error(text.to_string()),
+        b':' => RespValue::Integer(parse_int(text)?),
+        b'$' => {
+            let len = parse_int(text)?;
+            if len < 0 {
+                RespValue::BulkString(None)
+            } else {
+                let Some(bytes) = bulk_payload(input, used, len as usize)? else {
+                    return Ok(None);
+                };
+                used += bytes.len() + 2;
+                RespValue::BulkString(Some(bytes.to_vec()))
+            }
+        }
+        b'*' => {
+            let count = parse_int(text)?;
+            if count < 0 {
+                RespValue::Array(None)
+            } else {
+                if depth >= MAX_DEPTH {
+                    return Err(RespError(format!(
+                        "arrays nested more than {} deep",
+                        MAX_DEPTH
+                    )));
+                }
+                let mut items = Vec::new();
+                for _ in 0..count {
+                    match decode_nested(&input[used..], depth + 1)? {
+                        Some((item, item_len)) => {
+                            items.push(item);
+                            used += item_len;
+                        }
+                        None => return Ok(None),
+                    }
+                }
+                RespValue::Array(Some(items))
+            }
+        }
+        other => {
+            return Err(RespError(format!(
+                "unexpected type byte '{}'",
+                other.escape_ascii()
+            )));
+        }
+    };
+
+    Ok(Some((value, used)))
+}
+
+/// The `len` payload bytes of a bulk string starting at `start`, checking for the
+/// CRLF after them, or `None` if they haven't all arrived yet.
+fn bulk_payload(input: &[u8], start: usize, len: usize) -> Result<Option<&[u8]>, RespError> {
+    // Payload plus its trailing CRLF
+    if input.len() < start.saturating_add(len).saturating_add(2) {
+        return Ok(None);
+    }
+    if &input[start + len..start + len + 2] != b"\r\n" {
+        return Err(RespError("bulk string is missing its CRLF".to_string()));
+    }
+    Ok(Some(&input[start..start + len]))
+}
+
+fn line_text(body: &[u8]) -> Result<&str, RespError> {
+    std::str::from_utf8(body).map_err(|_| RespError("type line is not valid UTF-8".to_string()))
+}
+
+/// Finds the first CRLF-terminated line, returning it without the CRLF and the
+/// number of bytes consumed.
+fn read_line(input: &[u8]) -> Option<(&[u8], usize)> {
+    input
+        .windows(2)
+        .position(|window| window == b"\r\n")
+        .map(|end| (&input[..end], end + 2))
+}
+
+/// Like `read_line`, but a line that has gone on too long without ending is an
+/// error rather than something to wait for more of.
+fn header_line(input: &[u8]) -> Result<Option<(&[u8], usize)>, RespError> {
+    match read_line(input) {
+        None if input.len() > MAX_LINE_LEN => Err(RespError(format!(
+            "header line longer than {} bytes",
+            MAX_LINE_LEN
+        ))),
+        line => Ok(line),
+    }
+}
+
+fn parse_int(text: &str) -> Result<i64, RespError> {
+    text.parse()
+        .map_err(|_| RespError(format!("invalid integer '{}'", text)))
+}
+
+// Simple strings and errors can't contain line breaks
+fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(text: &str) -> RespValue {
+        RespValue::BulkString(Some(text.as_bytes().to_vec()))
+    }
+
+    fn round_trip(value: RespValue, encoded: &[u8]) {
+        assert_eq!(value.encode(), encoded);
+        assert_eq!(RespValue::decode(encoded), Ok(Some((value, encoded.len()))));
+    }
+
+    #[test]
+    fn simple_strings_round_trip() {
+        round_trip(RespValue::SimpleString("OK".to_string()), b"+OK\r\n");
+        round_trip(RespValue::SimpleString(String::new()), b"+\r\n");
+    }
+
+    #[test]
+    fn errors_round_trip() {
+        round_trip(
+            RespValue::Error("ERR unknown command".to_string()),
+            b"-ERR unknown command\r\n",
+        );
+    }
+
+    #[test]
+    fn integers_round_trip() {
+        round_trip(RespValue::Integer(0), b":0\r\n");
+        round_trip(RespValue::Integer(-42), b":-42\r\n");
+        round_trip(RespValue::Integer(i64::MAX), b":9223372036854775807\r\n");
+    }
+
+    #[test]
+    fn bulk_strings_round_trip() {
+        round_trip(bulk("hello"), b"$5\r\nhello\r\n");
+        round_trip(bulk(""), b"$0\r\n\r\n");
+        round_trip(bulk("a\r\nb"), b"$4\r\na\r\nb\r\n");
+        round_trip(RespValue::BulkString(None), b"$-1\r\n");
+    }
+
+    #[test]
+    fn arrays_round_trip() {
+        round_trip(RespValue::Array(Some(vec![])), b"*0\r\n");
+        round_trip(RespValue::Array(None), b"*-1\r\n");
+        round_trip(
+            RespValue::Array(Some(vec![
+                bulk("GET"),
+                RespValue::Integer(1),
+                RespValue::Array(Some(vec![RespValue::SimpleString("x".to_string())])),
+                RespValue::BulkString(None),
+            ])),
+            b"*4\r\n$3\r\nGET\r\n:1\r\n*1\r\n+x\r\n$-1\r\n",
+        );
+    }
+
+    #[test]
+    fn line_breaks_in_simple_strings_are_replaced() {
+        assert_eq!(
+            RespValue::Error("bad\r\nthing".to_string()).encode(),
+            b"-bad  thing\r\n"
+        );
+    }
+
+    #[test]
+    fn partial_values_wait_for_more_input() {
+        let encoded = b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n";
+        for end in 0..encoded.len() {
+            assert_eq!(RespValue::decode(&encoded[..end]), Ok(None), "{}", end);
+        }
+    }
+
+    #[test]
+    fn commands_decode_the_same_however_they_arrive() {
+        let args: Vec<&str> = vec!["SET", "key", "", "a longer value"];
+        let command = RespValue::Array(Some(args.iter().map(|arg| bulk(arg)).collect()));
+        let mut encoded = command.encode();
+        encoded.extend_from_slice(b"*1\r\n$4\r\nPING\r\n");
+        let expected: Vec<Vec<u8>> = args.iter().map(|arg| arg.as_bytes().to_vec()).collect();
+        let first_len = command.encode().len();
+
+        assert_eq!(
+            RespValue::decode_command(&encoded),
+            Ok(Some((expected.clone(), first_len)))
+        );
+
+        // One byte at a time, as if every read returned a single byte
+        let mut decoder = CommandDecoder::default();
+        for end in 0..first_len {
+            assert_eq!(decoder.decode(&encoded[..end]), Ok(None), "{}", end);
+        }
+        assert_eq!(
+            decoder.decode(&encoded[..first_len + 3]),
+            Ok(Some((expected, first_len)))
+        );
+        // The decoder starts over for the next command
+        assert_eq!(
+            decoder.decode(&encoded[first_len..]),
+            Ok(Some((vec![b"PING".to_vec()], encoded.len() - first_len)))
+        );
+    }
+
+    #[test]
+    fn commands_must_be_arrays_of_bulk_strings() {
+        for input in [
+            &b"+PING\r\n"[..],
+            b"*-1\r\n",
+            b"*1\r\n:1\r\n",
+            b"*1\r\n*1\r\n$1\r\na\r\n",
+            b"*1\r\n$-1\r\n",
+            b"*1\r\n$1\r\nab\r\n",
+            b"*x\r\n",
+        ] {
+            assert!(
+                RespValue::decode_command(input).is_err(),
+                "{}",
+                input.escape_ascii()
+            );
+        }
+    }
+
+    #[test]
+    fn header_lines_that_never_end_are_refused() {
+        let mut input = b"*1\r\n$".to_vec();
+        input.extend(std::iter::repeat_n(b'1', MAX_LINE_LEN));
+        assert!(RespValue::decode_command(&input).is_err());
+    }
+
+    #[test]
+    fn arrays_nested_too_deep_are_refused() {
+        let input = b"*1\r\n".repeat(MAX_DEPTH + 1);
+        assert!(RespValue::decode(&input).is_err());
+    }
+}
@@ -1,142 +1,560 @@
-use std::collections::HashMap;
-use std::io::Read;
-use std::net::{TcpListener, TcpStream};
-use std::sync::{Arc, Mutex}; // Will ensure that concurrent accesses will properly work
-use std::thread;
-use std::time::SystemTime;
+use std::io::{ErrorKind, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 use std::vec::Vec;
 
+use crate::commands::client::{ClientState, ServerContext};
 use crate::commands::defs::{execute, match_command};
+use crate::commands::reply::Reply;
+use crate::commands::tokenize::tokenize;
+use crate::config::Config;
+use crate::connections::{self, ConnectionInfo, Connections};
+use crate::pubsub::{Message, PubSub};
+use crate::server::framing::{Frame, FrameReader};
+use crate::server::pool::ThreadPool;
+use crate::server::resp::RespValue;
 use crate::server::util;
+use crate::stats::ServerStats;
+use crate::store::aof::{self, Aof};
+use crate::store::{self, Store, persist};
+use crate::time;
+use crate::{debug, error, info, warn};
 
-#[derive(Debug, Clone)]
-pub enum ConnectionStatus {
-    Active,
-    Disconnected,
-} // Connection statuses
-
-#[derive(Debug)]
-pub struct ConnectionInfo {
-    status: ConnectionStatus,
-    address: String,
-    connected_at: SystemTime,
-    last_activity: SystemTime,
+// How often the background sweeper removes expired keys
+const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_millis(100);
+// How long an idle listener waits before checking for shutdown again
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+// How often idle connections are looked for when reaping is enabled
+const IDLE_REAP_INTERVAL: Duration = Duration::from_millis(100);
+// How long a subscribed client's read waits before checking for published messages
+const PUBSUB_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// State shared by every listener and connection of one server.
+pub struct ServerState {
+    // Shared across listeners so connection ids stay unique server-wide
+    next_id: AtomicU64,
+    shutdown: AtomicBool,
+    // Databases and services commands reach through their connection
+    context: Arc<ServerContext>,
+}
+
+/// Handle to a running server, used to wait for it or shut it down.
+pub struct ServerHandle {
+    local_addrs: Vec<SocketAddr>,
+    state: Arc<ServerState>,
+    accept_threads: Vec<JoinHandle<()>>,
+    pool: Arc<ThreadPool>,
 }
 
-pub fn start_server(port: i32) {
-    println!("Starting server on port {port}");
+impl ServerHandle {
+    /// Addresses the server is actually bound to (useful when binding port 0).
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        &self.local_addrs
+    }
 
-    let listener =
-        TcpListener::bind(format!("127.0.0.1:{port}")).expect("Failed to bind on the port");
+    /// Stops accepting connections, lets every client finish the command it is
+    /// running, then closes them and waits for the workers to exit.
+    pub fn shutdown(self) {
+        info!("Shutting down");
+        self.state.shutdown.store(true, Ordering::SeqCst);
 
-    // Arc allows for multiple ownership, Mutex allows for safe mutation across threads.
-    let connections: Arc<Mutex<HashMap<u64, ConnectionInfo>>> =
-        Arc::new(Mutex::new(HashMap::new()));
-    let mut next_id: u64 = 0u64;
+        for handle in self.accept_threads {
+            if handle.join().is_err() {
+                error!("A listener thread panicked");
+            }
+        }
+
+        // Closing the read side wakes clients blocked in read(); one that is mid-command
+        // still writes its reply before noticing the end of input. Connections still
+        // queued for a worker see the end of input as soon as they start.
+        self.state.context.connections.close_reads();
+
+        // The listeners have released their references, so this drops the pool and
+        // joins its workers once the queue drains
+        drop(self.pool);
+    }
+
+    /// Blocks until every listener has stopped.
+    pub fn wait(self) {
+        for handle in self.accept_threads {
+            if handle.join().is_err() {
+                error!("A listener thread panicked");
+            }
+        }
+    }
+}
 
-    for stream in listener.incoming() {
-        let stream = stream.expect("Stream error!");
-        let id = next_id;
-        next_id += 1;
+pub fn start_server(ports: &[u16], config: &Config) {
+    match spawn_server(ports, config) {
+        Ok(handle) => handle.wait(),
+        Err(e) => {
+            error!("Failed to start the server -- {}", e);
+            std::process::exit(1);
+        }
+    }
+}
 
-        // Get the peer address
-        let addr = stream
-            .peer_addr()
-            .expect("Error with the peer address")
-            .to_string();
+/// Binds every port on every configured address and starts accepting connections
+/// in the background.
+pub fn spawn_server(ports: &[u16], config: &Config) -> std::io::Result<ServerHandle> {
+    // Load saved data before binding, so bad data stops startup before clients connect.
+    // The append-only file is more up to date than a snapshot, so it wins when enabled.
+    let databases: Arc<[Arc<Store>]> = (0..config.databases.max(1))
+        .map(|_| {
+            Arc::new(Store::with_shards(
+                config.shards,
+                config.maxkeys,
+                config.eviction_policy,
+            ))
+        })
+        .collect();
+    if let Some(path) = &config.appendonly
+        && path.exists()
+    {
+        let replayed = replay_aof(path, &databases, config).map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!("Failed to replay {} -- {}", path.display(), e),
+            )
+        })?;
+        info!("Replayed {} commands from {}", replayed, path.display());
+    } else if let Some(path) = &config.snapshot_path
+        && path.exists()
+    {
+        persist::load(path, &databases).map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!("Failed to load snapshot {} -- {}", path.display(), e),
+            )
+        })?;
+        info!("Loaded snapshot from {}", path.display());
+    }
 
-        let connections_clone = Arc::clone(&connections);
+    let aof = match &config.appendonly {
+        Some(path) => Some(Arc::new(Aof::open(path, config.appendfsync)?)),
+        None => None,
+    };
+    // Evicted keys are logged as deleted so a replay doesn't bring them back
+    if let Some(aof) = &aof {
+        for (db, store) in databases.iter().enumerate() {
+            let aof = Arc::clone(aof);
+            store.on_evict(move |key| {
+                if let Err(e) = aof.append(db, &["DEL", key]) {
+                    error!("Error writing to the append-only file -- {}", e);
+                }
+            });
+        }
+    }
+
+    // Bind everything up front so a bad address or port fails before any client is accepted
+    if config.bind.is_empty() {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "No bind address configured",
+        ));
+    }
+    let ips = config
+        .bind
+        .iter()
+        .map(|bind| parse_bind_address(bind))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let mut listeners: Vec<(String, TcpListener)> = Vec::new();
+    for ip in &ips {
+        for port in ports {
+            let listener = TcpListener::bind(SocketAddr::new(*ip, *port))?;
+            // Non-blocking so the accept loop can notice a shutdown request
+            listener.set_nonblocking(true)?;
+            let endpoint = listener.local_addr()?.to_string();
+            info!("Listening on {endpoint}");
+            listeners.push((endpoint, listener));
+        }
+    }
+
+    let local_addrs = listeners
+        .iter()
+        .map(|(_, listener)| listener.local_addr())
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    store::spawn_expiry_sweeper(&databases, EXPIRY_SWEEP_INTERVAL);
+
+    // Arc allows for multiple ownership
+    let state = Arc::new(ServerState {
+        next_id: AtomicU64::new(0),
+        shutdown: AtomicBool::new(false),
+        context: Arc::new(ServerContext {
+            databases,
+            pubsub: Arc::new(PubSub::new()),
+            connections: Arc::new(Connections::new()),
+            aof,
+            stats: Arc::new(ServerStats::new()),
+            requirepass: config.requirepass.clone(),
+            snapshot_path: config.snapshot_path.clone(),
+            config: Arc::new(RwLock::new(config.clone())),
+        }),
+    });
+    if let Some(threshold) = config.reap_idle_after {
+        connections::spawn_idle_reaper(&state.context.connections, threshold, IDLE_REAP_INTERVAL);
+    }
+    // One pool for every listener bounds the total number of client threads.
+    // It lives outside the shared state so no worker ever holds the last reference.
+    let pool = Arc::new(ThreadPool::new(config.workers));
+
+    let accept_threads = listeners
+        .into_iter()
+        .map(|(endpoint, listener)| {
+            let state = Arc::clone(&state);
+            let pool = Arc::clone(&pool);
+            thread::spawn(move || accept_loop(listener, endpoint, state, pool))
+        })
+        .collect();
+
+    Ok(ServerHandle {
+        local_addrs,
+        state,
+        accept_threads,
+        pool,
+    })
+}
+
+/// Parses the configured interface address, accepting IPv6 with or without brackets.
+fn parse_bind_address(bind: &str) -> std::io::Result<IpAddr> {
+    let literal = bind
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .unwrap_or(bind);
+    literal.parse().map_err(|_| {
+        std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid bind address '{}'", bind),
+        )
+    })
+}
+
+/// Accepts connections until shutdown is requested, queueing each one on the pool.
+///
+/// A connection keeps its worker until it closes, so once every worker is taken
+/// new connections wait in the queue and are served as earlier ones close. The
+/// queue is bounded by `maxclients`, which counts waiting connections too.
+fn accept_loop(
+    listener: TcpListener,
+    endpoint: String,
+    state: Arc<ServerState>,
+    pool: Arc<ThreadPool>,
+) {
+    while !state.shutdown.load(Ordering::SeqCst) {
+        let (stream, peer) = match listener.accept() {
+            Ok(accepted) => accepted,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+                continue;
+            }
+            Err(e) => {
+                error!("Error accepting on {} -- {}", endpoint, e);
+                continue;
+            }
+        };
+
+        // Accepted sockets should block even though the listener doesn't
+        if let Err(e) = stream.set_nonblocking(false) {
+            error!("Error configuring connection on {} -- {}", endpoint, e);
+            continue;
+        }
+
+        let id = state.next_id.fetch_add(1, Ordering::SeqCst);
+        // From accept itself, so a peer that has already reset can't fail this
+        let addr = peer.to_string();
+
+        let control = match stream.try_clone() {
+            Ok(control) => control,
+            Err(e) => {
+                error!("Error cloning connection {} -- {}", id, e);
+                continue;
+            }
+        };
+
+        let state_clone = Arc::clone(&state);
 
         // Add to the connections pool
+        let info = ConnectionInfo::new(addr.clone(), endpoint.clone(), control);
+        if !state
+            .context
+            .connections
+            .admit(id, info, state.context.settings().maxclients)
         {
-            let mut conns = connections_clone.lock().unwrap();
-            conns.insert(
-                id,
-                ConnectionInfo {
-                    status: ConnectionStatus::Active,
-                    address: addr.clone(),
-                    connected_at: SystemTime::now(),
-                    last_activity: SystemTime::now(),
-                },
+            warn!(
+                "Rejected connection {} on {}: {} -- too many clients",
+                id, endpoint, addr
             );
+            state
+                .context
+                .stats
+                .rejected_connections
+                .fetch_add(1, Ordering::SeqCst);
+            // Dropping the writer flushes the reply and closes the socket
+            let mut writer = util::ConnectionWriter::new(stream);
+            let reply = RespValue::Error("ERR max number of clients reached".to_string());
+            let _ = util::send_resp(&reply, &mut writer);
+            continue;
         }
 
-        println!("New connection {}: {}", id, addr);
+        state
+            .context
+            .stats
+            .connections_received
+            .fetch_add(1, Ordering::SeqCst);
+
+        info!("New connection {} on {}: {}", id, endpoint, addr);
 
-        thread::spawn(move || {
-            let result = handle_client(id, stream, &connections_clone);
+        pool.execute(move || {
+            // Clean up even if the connection's thread panics, so it isn't left
+            // counting towards maxclients
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| handle_client(id, stream, &state_clone)))
+                    .unwrap_or_else(|_| Err(std::io::Error::other("connection thread panicked")));
+            state_clone.context.pubsub.unsubscribe_all(id);
 
             // Clean up when done
-            // Curly braces ensure the lock goes away after this block
-            {
-                let mut conns = connections_clone.lock().unwrap();
-                conns.remove(&id);
+            match state_clone.context.connections.remove(id) {
+                Some(info) => info!(
+                    "Connection {} ({} via {}) closed after {}s: {:?}",
+                    id,
+                    info.address(),
+                    info.endpoint(),
+                    time::elapsed_since(info.connected_at()).as_secs(),
+                    result
+                ),
+                None => info!("Connection {} closed: {:?}", id, result),
             }
-
-            println!("Connection {} closed: {:?}", id, result);
         });
     }
+
+    info!("Stopped listening on {}", endpoint);
 }
 
-pub fn handle_client(
-    id: u64,
-    mut stream: TcpStream,
-    connections: &Arc<Mutex<HashMap<u64, ConnectionInfo>>>,
-) -> std::io::Result<()> {
-    println!("Handling the client {}", id);
+pub fn handle_client(id: u64, stream: TcpStream, state: &ServerState) -> std::io::Result<()> {
+    debug!("Handling the client {}", id);
 
-    let mut buffer = [0; 1024]; // 1 kb buffer
+    // A client that stays silent this long is disconnected
+    stream.set_read_timeout(state.context.settings().idle_timeout)?;
+
+    let mut writer = util::ConnectionWriter::new(stream.try_clone()?);
+    let (messages, inbox) = mpsc::channel();
+    let mut client = ClientState::new(id, Arc::clone(&state.context), messages);
+    let mut frames = FrameReader::new(stream);
+    // Whether the client last spoke RESP, so pushed messages match its protocol
+    let mut resp = false;
+    let mut polling = false;
 
     loop {
-        match stream.read(&mut buffer) {
-            Ok(0) => {
-                println!("Client {} disconnected", id);
-                {
-                    let mut conns = connections.lock().unwrap();
-                    if let Some(info) = conns.get_mut(&id) {
-                        info.status = ConnectionStatus::Disconnected;
-                    }
-                }
+        // A subscribed client waits on two things at once: its socket and published
+        // messages. Reads time out briefly so queued messages can be sent in between.
+        let subscribed = !client.subscriptions.is_empty();
+        if subscribed != polling {
+            let timeout = if subscribed {
+                Some(PUBSUB_POLL_INTERVAL)
+            } else {
+                state.context.settings().idle_timeout
+            };
+            frames.get_ref().set_read_timeout(timeout)?;
+            polling = subscribed;
+        }
+
+        // Replies wait in the writer while pipelined commands are still buffered, and
+        // go out together before blocking on the client for more
+        let frame = match frames.buffered_frame() {
+            Ok(None) => writer.flush().and_then(|()| frames.next_frame()),
+            frame => frame,
+        };
+
+        match frame {
+            Ok(None) => {
+                info!("Client {} disconnected", id);
+                state.context.connections.mark_disconnected(id);
                 break;
             }
-            Ok(n) => {
-                // Got n bytes of data
-                let received = String::from_utf8_lossy(&buffer[..n]);
-                println!("Client {} sent: {}", id, received.trim());
-
-                // Handle the input - convert errors to error messages
-                let output = match handle_input(received.to_string()) {
-                    Ok(result) => result,
-                    Err(e) => format!("Error: {}", e),
+            Ok(Some(Frame::Inline(frame))) => {
+                resp = false;
+
+                // Decode once the whole command has arrived, so multi-byte characters
+                // split across reads stay intact
+                let received = match utf8(frame) {
+                    Ok(received) => received,
+                    Err(e) => {
+                        util::send(Reply::from_error(&e).render().as_bytes(), &mut writer)?;
+                        state.context.connections.touch(id);
+                        continue;
+                    }
                 };
 
+                // Like Redis, a blank line (e.g. a stray enter in telnet) gets no reply
+                if received.trim().is_empty() {
+                    state.context.connections.touch(id);
+                    continue;
+                }
+
+                debug!("Client {} sent: {}", id, received.trim());
+
+                let reply = handle_input(received, state, &mut client)
+                    .unwrap_or_else(|e| Reply::from_error(&e));
+
                 // Send the result (or error message) back to the client
-                util::send(&output, &mut stream)?;
+                util::send(reply.render().as_bytes(), &mut writer)?;
 
-                // Update last activity
+                state.context.connections.touch(id);
+            }
+            Ok(Some(Frame::Resp(args))) => {
+                resp = true;
+                let reply = match args
+                    .into_iter()
+                    .map(utf8)
+                    .collect::<std::io::Result<Vec<_>>>()
                 {
-                    let mut conns = connections.lock().unwrap();
-                    if let Some(info) = conns.get_mut(&id) {
-                        info.last_activity = SystemTime::now();
+                    Ok(args) => {
+                        debug!("Client {} sent: {}", id, args.join(" "));
+                        let parts: Vec<&str> = args.iter().map(String::as_str).collect();
+                        dispatch(&parts, state, &mut client)
                     }
+                    Err(e) => Err(e),
                 }
+                .unwrap_or_else(|e| Reply::from_error(&e));
+
+                util::send_resp(&reply.to_resp(), &mut writer)?;
+                state.context.connections.touch(id);
+            }
+            // Read timeouts surface as WouldBlock or TimedOut depending on the platform
+            Err(e)
+                if polling && matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                info!("Client {} timed out after being idle", id);
+                state.context.connections.mark_disconnected(id);
+                break;
             }
             Err(e) => {
-                eprintln!("Error reading from client {} -- {}", id, e);
+                warn!("Error reading from client {} -- {}", id, e);
+                // Tell the client why before hanging up on malformed input
+                if e.kind() == ErrorKind::InvalidData {
+                    let _ = util::send_resp(&Reply::from_error(&e).to_resp(), &mut writer);
+                }
                 return Err(e);
             }
         }
+
+        if client.quit {
+            info!("Client {} quit", id);
+            state.context.connections.mark_disconnected(id);
+            break;
+        }
+
+        deliver_messages(&inbox, resp, &mut writer)?;
     }
 
     Ok(())
 }
 
-fn handle_input(input: String) -> std::io::Result<String> {
-    let parts: Vec<&str> = input.split(" ").collect();
+/// Sends every message published to the client's channels since the last call.
+fn deliver_messages(
+    inbox: &Receiver<Message>,
+    resp: bool,
+    writer: &mut util::ConnectionWriter,
+) -> std::io::Result<()> {
+    for message in inbox.try_iter() {
+        let push = Reply::list(["message", &message.channel, &message.payload]);
+        if resp {
+            util::send_resp(&push.to_resp(), writer)?;
+        } else {
+            util::send(push.render().as_bytes(), writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Commands and stored values are strings, so input that isn't valid UTF-8 is
+/// refused outright rather than having its bad bytes replaced.
+fn utf8(bytes: Vec<u8>) -> std::io::Result<String> {
+    String::from_utf8(bytes).map_err(|_| {
+        std::io::Error::new(
+            ErrorKind::InvalidData,
+            "argument is not valid UTF-8; only UTF-8 strings are supported",
+        )
+    })
+}
+
+fn handle_input(
+    input: String,
+    state: &ServerState,
+    client: &mut ClientState,
+) -> std::io::Result<Reply> {
+    let tokens = tokenize(&input)?;
+    let parts: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    dispatch(&parts, state, client)
+}
+
+/// Rebuilds `databases` by running every command logged in the append-only file,
+/// returning how many there were.
+///
+/// A logged command can fail when run again even though the log is intact, for
+/// example LSET on a list that has since expired. Those are skipped with a warning
+/// so startup only stops on a file that can't be read.
+fn replay_aof(
+    path: &Path,
+    databases: &Arc<[Arc<Store>]>,
+    config: &Config,
+) -> std::io::Result<usize> {
+    // Replay runs as a trusted client with nowhere to log to or publish from
+    let (messages, _inbox) = mpsc::channel();
+    let context = ServerContext {
+        databases: Arc::clone(databases),
+        pubsub: Arc::new(PubSub::new()),
+        connections: Arc::new(Connections::new()),
+        aof: None,
+        stats: Arc::new(ServerStats::new()),
+        requirepass: None,
+        snapshot_path: None,
+        config: Arc::new(RwLock::new(config.clone())),
+    };
+    let mut client = ClientState::new(0, Arc::new(context), messages);
+
+    aof::replay(path, |command| {
+        let parts: Vec<&str> = command.iter().map(String::as_str).collect();
+        let Some((name, args)) = parts.split_first() else {
+            return Ok(());
+        };
+
+        let store = &databases[client.db];
+        let replayed = match_command(name)
+            .and_then(|handler| execute(handler, args, store, &mut client).map(|_| ()));
+        if let Err(e) = replayed {
+            warn!("Skipping {} from {} -- {}", name, path.display(), e);
+        }
+        Ok(())
+    })
+}
+
+/// Runs a command given as its name followed by its arguments, against the
+/// database the client has selected.
+fn dispatch(
+    parts: &[&str],
+    state: &ServerState,
+    client: &mut ClientState,
+) -> std::io::Result<Reply> {
+    let Some((name, args)) = parts.split_first() else {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "Empty command",
+        ));
+    };
 
-    let cmd_type = match_command(parts[0])?;
-    let args: &[&str] = &parts[1..];
-    let output = execute(&cmd_type, args)?;
+    // An unknown command inside MULTI dooms the whole transaction
+    let handler = match_command(name).inspect_err(|_| client.fail_transaction())?;
+    state.context.stats.record_command();
+    let store = &state.context.databases[client.db];
+    let output = execute(handler, args, store, client)?;
 
     Ok(output)
 }
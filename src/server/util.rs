@@ -1,9 +1,119 @@
-use std::io::Write;
-use std::net::TcpStream;
-
-pub fn send(message: &str, client_stream: &mut TcpStream) -> Result<(), std::io::Error> {
-    let fixed_message: String = message.to_string() + "\n";
-    client_stream.write_all(fixed_message.as_bytes())?;
-    client_stream.flush()?;
-    return Ok(());
+use std::io::{BufWriter, Write};
+use std::net::{Shutdown, TcpStream};
+
+use crate::server::resp::RespValue;
+
+/// Writes `message` followed by a newline. The two are written separately rather
+/// than joined first, so a large value is never copied.
+///
+/// Nothing is flushed, so replies to pipelined commands can go out together; the
+/// caller flushes before waiting on the client again.
+pub fn send(message: &[u8], client_stream: &mut impl Write) -> Result<(), std::io::Error> {
+    client_stream.write_all(message)?;
+    client_stream.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Writes an encoded RESP reply, leaving flushing to the caller like `send`.
+pub fn send_resp(reply: &RespValue, client_stream: &mut impl Write) -> Result<(), std::io::Error> {
+    client_stream.write_all(&reply.encode())?;
+    Ok(())
+}
+
+/// Buffered write half of a client connection.
+///
+/// Replies collect in the buffer until `flush`, so a batch costs one write to the
+/// socket rather than one per reply. Dropping it flushes anything still pending and
+/// shuts the socket down, so early returns from the handler can't lose a reply.
+/// Errors at that point are only logged.
+pub struct ConnectionWriter {
+    stream: BufWriter<TcpStream>,
+}
+
+impl ConnectionWriter {
+    pub fn new(stream: TcpStream) -> Self {
+        Self {
+            stream: BufWriter::new(stream),
+        }
+    }
+}
+
+impl Write for ConnectionWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Drop for ConnectionWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.stream.flush() {
+            crate::warn!("Error flushing connection on close -- {}", e);
+        }
+
+        // The peer may already have hung up, in which case there is nothing to shut down
+        match self.stream.get_ref().shutdown(Shutdown::Both) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotConnected => {}
+            Err(e) => crate::warn!("Error shutting down connection -- {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    use super::*;
+
+    fn connected_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (server, client)
+    }
+
+    #[test]
+    fn dropping_the_writer_flushes_and_closes() {
+        let (server, mut client) = connected_pair();
+        let mut writer = ConnectionWriter::new(server);
+        send(b"first", &mut writer).unwrap();
+        writer.flush().unwrap();
+        // Flushing again with nothing pending is harmless
+        writer.flush().unwrap();
+        send(b"second", &mut writer).unwrap();
+        drop(writer);
+
+        let mut received = Vec::new();
+        client.read_to_end(&mut received).unwrap();
+        assert_eq!(received, b"first\nsecond\n");
+    }
+
+    #[test]
+    fn dropping_the_writer_after_the_peer_left_does_not_panic() {
+        let (server, client) = connected_pair();
+        drop(client);
+        let mut writer = ConnectionWriter::new(server);
+        let _ = send(b"nobody is listening", &mut writer);
+        drop(writer);
+    }
+
+    #[test]
+    fn large_payloads_arrive_whole_with_their_terminator() {
+        let (server, mut client) = connected_pair();
+        let payload: Vec<u8> = (0..1_000_000).map(|i| (i % 251) as u8).collect();
+        let mut writer = ConnectionWriter::new(server);
+        send(&payload, &mut writer).unwrap();
+        drop(writer);
+
+        let mut received = Vec::new();
+        client.read_to_end(&mut received).unwrap();
+        assert_eq!(received.len(), payload.len() + 1);
+        assert_eq!(&received[..payload.len()], &payload[..]);
+        assert_eq!(received.last(), Some(&b'\n'));
+    }
 }
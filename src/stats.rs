@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::locks::MutexExt;
+
+/// Call count and latency of one command, as reported by INFO commandstats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommandStats {
+    pub calls: u64,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl CommandStats {
+    pub fn record(&mut self, latency: Duration) {
+        self.min = if self.calls == 0 {
+            latency
+        } else {
+            self.min.min(latency)
+        };
+        self.max = self.max.max(latency);
+        self.total += latency;
+        self.calls += 1;
+    }
+
+    pub fn average(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::ZERO
+        } else {
+            self.total.div_f64(self.calls as f64)
+        }
+    }
+}
+
+/// Counters describing a running server, as reported by INFO.
+#[derive(Debug)]
+pub struct ServerStats {
+    pub started_at: SystemTime,
+    pub connections_received: AtomicU64,
+    // Connections turned away because maxclients was reached
+    pub rejected_connections: AtomicU64,
+    pub commands_processed: AtomicU64,
+    // Latency per command name, kept sorted so INFO lists them in a stable order.
+    // Guarded separately from any store so recording never waits on data access.
+    commands: Mutex<BTreeMap<&'static str, CommandStats>>,
+}
+
+impl ServerStats {
+    pub fn new() -> Self {
+        Self {
+            started_at: SystemTime::now(),
+            connections_received: AtomicU64::new(0),
+            rejected_connections: AtomicU64::new(0),
+            commands_processed: AtomicU64::new(0),
+            commands: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn record_command(&self) {
+        self.commands_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Adds one run of `name` that took `latency`.
+    pub fn record_latency(&self, name: &'static str, latency: Duration) {
+        self.commands
+            .lock_unpoisoned()
+            .entry(name)
+            .or_default()
+            .record(latency);
+    }
+
+    /// Stats for every command run at least once, ordered by name.
+    pub fn command_stats(&self) -> Vec<(&'static str, CommandStats)> {
+        let commands = self.commands.lock_unpoisoned();
+        commands
+            .iter()
+            .map(|(name, stats)| (*name, *stats))
+            .collect()
+    }
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,123 @@
+//! Append-only file logging of write commands.
+//!
+//! Every successful write is appended as a RESP array of its name and arguments,
+//! preceded by a `SELECT` whenever it targets a different database than the entry
+//! before it. Replaying the file from the start rebuilds the data. Timeouts are
+//! logged as absolute PEXPIREAT deadlines, so a replay doesn't extend or revive keys.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::FsyncPolicy;
+use crate::locks::MutexExt;
+use crate::server::resp::RespValue;
+
+// How long EverySec lets written data go without an fsync
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An open append-only file, shared by every connection of a server.
+#[derive(Debug)]
+pub struct Aof {
+    inner: Mutex<AofFile>,
+}
+
+#[derive(Debug)]
+struct AofFile {
+    file: File,
+    policy: FsyncPolicy,
+    // Database of the last logged command, so SELECT is only logged on a change
+    db: Option<usize>,
+    last_sync: Instant,
+}
+
+impl Aof {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: &Path, policy: FsyncPolicy) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            inner: Mutex::new(AofFile {
+                file,
+                policy,
+                db: None,
+                last_sync: Instant::now(),
+            }),
+        })
+    }
+
+    /// Logs `command` (its name followed by its arguments) as run against `db`.
+    pub fn append(&self, db: usize, command: &[&str]) -> io::Result<()> {
+        let mut aof = self.inner.lock_unpoisoned();
+
+        let mut entry = Vec::new();
+        if aof.db != Some(db) {
+            entry.extend(encode(&["SELECT", &db.to_string()]));
+        }
+        entry.extend(encode(command));
+
+        // One write per entry keeps concurrent writers from interleaving
+        aof.file.write_all(&entry)?;
+        aof.db = Some(db);
+
+        let sync = match aof.policy {
+            FsyncPolicy::Always => true,
+            FsyncPolicy::EverySec => aof.last_sync.elapsed() >= SYNC_INTERVAL,
+            FsyncPolicy::No => false,
+        };
+        if sync {
+            aof.file.sync_data()?;
+            aof.last_sync = Instant::now();
+        }
+
+        Ok(())
+    }
+}
+
+fn encode(command: &[&str]) -> Vec<u8> {
+    RespValue::Array(Some(
+        command
+            .iter()
+            .map(|part| RespValue::BulkString(Some(part.as_bytes().to_vec())))
+            .collect(),
+    ))
+    .encode()
+}
+
+/// Reads the file at `path` and passes each logged command to `apply`, in order.
+/// Returns how many commands were replayed.
+///
+/// A file that doesn't parse, including one whose last entry was cut short, is
+/// reported as `InvalidData` rather than partially applied past the damage.
+pub fn replay(
+    path: &Path,
+    mut apply: impl FnMut(&[String]) -> io::Result<()>,
+) -> io::Result<usize> {
+    let data = fs::read(path)?;
+    let mut offset = 0;
+    let mut count = 0;
+
+    while offset < data.len() {
+        let (args, used) = RespValue::decode_command(&data[offset..])
+            .map_err(|e| corrupt(format!("{} at byte {}", e, offset)))?
+            .ok_or_else(|| corrupt(format!("entry at byte {} is cut short", offset)))?;
+        let command: Vec<String> = args
+            .iter()
+            .map(|part| String::from_utf8_lossy(part).into_owned())
+            .collect();
+
+        apply(&command)?;
+        offset += used;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn corrupt(message: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("corrupt append-only file: {}", message),
+    )
+}
@@ -0,0 +1,152 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::store::value::Value;
+
+/// The values of one shard, looked up by key and also kept in SCAN order.
+///
+/// Reads go straight to the map through `Deref`. Anything that adds or removes a
+/// key goes through the methods here, so the order can't fall out of step, and
+/// neither can the key count shared with the other shards of the store.
+#[derive(Debug, Default)]
+pub struct Entries {
+    values: HashMap<String, Value>,
+    // Every key with its scan position, in the order SCAN visits them
+    order: BTreeSet<(u64, String)>,
+    // Keys held by every shard of the store, plus places claimed by `reserve`
+    total: Arc<AtomicUsize>,
+    // Places claimed in `total` that the next new keys here will take
+    reserved: usize,
+}
+
+impl Deref for Entries {
+    type Target = HashMap<String, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl Entries {
+    /// No entries yet, counting keys together with these and every other sibling.
+    pub fn sibling(&self) -> Self {
+        Self {
+            total: Arc::clone(&self.total),
+            ..Self::default()
+        }
+    }
+
+    /// Keys held across every shard sharing the count, including claimed places.
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::SeqCst)
+    }
+
+    /// Claims a place in the shared count for a new key unless `limit` are already
+    /// taken. The next key inserted here takes the place; any still unused when the
+    /// caller lets go of the shard must be given back with `release`.
+    pub fn reserve(&mut self, limit: usize) -> bool {
+        let claimed = self
+            .total
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |total| {
+                (total < limit).then_some(total + 1)
+            })
+            .is_ok();
+        if claimed {
+            self.reserved += 1;
+        }
+        claimed
+    }
+
+    /// Places claimed by `reserve` that no key has taken yet.
+    pub fn reserved(&self) -> usize {
+        self.reserved
+    }
+
+    /// Gives back every place claimed by `reserve` that no key has taken.
+    pub fn release(&mut self) {
+        self.total
+            .fetch_sub(std::mem::take(&mut self.reserved), Ordering::SeqCst);
+    }
+
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        let previous = self.place(key, value);
+        if previous.is_none() {
+            if self.reserved > 0 {
+                self.reserved -= 1;
+            } else {
+                self.total.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let value = self.detach(key)?;
+        self.total.fetch_sub(1, Ordering::SeqCst);
+        Some(value)
+    }
+
+    /// Removes `key` but leaves its place in the shared count, for `attach` to hand
+    /// on to the key it moves to.
+    pub fn detach(&mut self, key: &str) -> Option<Value> {
+        let value = self.values.remove(key)?;
+        self.order
+            .remove(&(super::scan_position(key), key.to_string()));
+        Some(value)
+    }
+
+    /// Stores a value taken out by `detach` under `key`, which must be missing.
+    pub fn attach(&mut self, key: String, value: Value) {
+        let previous = self.place(key, value);
+        debug_assert!(previous.is_none(), "attached over an existing key");
+    }
+
+    fn place(&mut self, key: String, value: Value) -> Option<Value> {
+        self.order.insert((super::scan_position(&key), key.clone()));
+        self.values.insert(key, value)
+    }
+
+    /// The value at `key`, storing `empty()` there first if it is missing.
+    pub fn get_or_insert_with(&mut self, key: &str, empty: fn() -> Value) -> &mut Value {
+        if !self.values.contains_key(key) {
+            self.insert(key.to_string(), empty());
+        }
+        self.values.get_mut(key).expect("key was just inserted")
+    }
+
+    /// The value at `key` for changing in place. The key itself stays put.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.values.get_mut(key)
+    }
+
+    pub fn clear(&mut self) {
+        self.total.fetch_sub(self.values.len(), Ordering::SeqCst);
+        self.values.clear();
+        self.order.clear();
+    }
+
+    /// Keys from scan position `from` on, in order, that `keep` accepts: at least
+    /// `count` of them while any remain, plus any more at the same position as the
+    /// last. Also returns the position to carry on from, or None once none are left.
+    pub fn page(
+        &self,
+        from: u64,
+        count: usize,
+        keep: impl Fn(&str) -> bool,
+    ) -> (Vec<String>, Option<u64>) {
+        let mut keys = Vec::new();
+        let mut last = None;
+        for (position, key) in self.order.range((from, String::new())..) {
+            if keys.len() >= count && last != Some(*position) {
+                return (keys, Some(*position));
+            }
+            if keep(key) {
+                keys.push(key.clone());
+                last = Some(*position);
+            }
+        }
+        (keys, None)
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+
+/// What a store does when a write would take it past its key limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    // Refuse the write with an OOM error
+    #[default]
+    NoEviction,
+    // Drop the key that was used least recently
+    AllKeysLru,
+    // Drop any key at random
+    AllKeysRandom,
+}
+
+impl EvictionPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvictionPolicy::NoEviction => "noeviction",
+            EvictionPolicy::AllKeysLru => "allkeys-lru",
+            EvictionPolicy::AllKeysRandom => "allkeys-random",
+        }
+    }
+}
+
+impl fmt::Display for EvictionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EvictionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "noeviction" => Ok(EvictionPolicy::NoEviction),
+            "allkeys-lru" => Ok(EvictionPolicy::AllKeysLru),
+            "allkeys-random" => Ok(EvictionPolicy::AllKeysRandom),
+            _ => Err(format!("unknown eviction policy '{}'", s)),
+        }
+    }
+}
+
+/// A pseudo-random index below `len`, which must be non-zero.
+///
+/// Seeded from the standard library's per-map random keys, which is plenty for
+/// picking victims without pulling in a random number crate.
+pub fn random_index(len: usize) -> usize {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_usize(len);
+    (hasher.finish() % len as u64) as usize
+}
@@ -0,0 +1,1750 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut, RangeInclusive};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::glob::glob_match;
+use crate::locks::RwLockExt;
+use crate::time;
+use entries::Entries;
+
+pub mod aof;
+mod entries;
+mod eviction;
+pub mod persist;
+mod value;
+
+pub use eviction::EvictionPolicy;
+pub use value::{ListEnd, SetOperation, Value};
+
+/// In-memory key-value store shared by every connection.
+///
+/// Locking strategy: keys are hashed into a fixed number of shards, each a
+/// `Keyspace` behind its own `RwLock`, so commands on unrelated keys rarely wait on
+/// each other. Each method takes the lock of the shard it needs for the duration of
+/// one operation and releases it before returning, so a single call is atomic.
+/// Methods that only read share the lock; anything that changes data takes it
+/// exclusively. Commands that need several steps on one key to be atomic together
+/// should use `lock` (or `read`) and work on that key's `Keyspace` while holding the
+/// guard. Several keys are locked together with `lock_keys` or `read_keys`, which
+/// always take shards in ascending order so concurrent callers can't deadlock.
+#[derive(Debug)]
+pub struct Store {
+    shards: Box<[RwLock<Keyspace>]>,
+    on_evict: OnceLock<EvictionHook>,
+}
+
+// Told about each key evicted to make room, so it can be recorded elsewhere
+#[derive(Clone)]
+struct EvictionHook(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl fmt::Debug for EvictionHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EvictionHook")
+    }
+}
+
+/// Number of shards in a store unless configured otherwise.
+pub const DEFAULT_SHARDS: usize = 16;
+
+/// Remaining lifetime of a key, as reported by TTL-style commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ttl {
+    Missing,
+    Persistent,
+    Expires(Duration),
+}
+
+/// Errors from store operations that can't be applied to the current value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreError {
+    NotAnInteger,
+    Overflow,
+    WrongType,
+    OutOfMemory,
+    InvalidUtf8,
+    NoSuchKey,
+    IndexOutOfRange,
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            StoreError::NotAnInteger => "value is not an integer or out of range",
+            StoreError::Overflow => "increment or decrement would overflow",
+            StoreError::WrongType => {
+                "WRONGTYPE Operation against a key holding the wrong kind of value"
+            }
+            StoreError::OutOfMemory => "OOM command not allowed when the key limit is reached",
+            StoreError::InvalidUtf8 => "the result would split a multi-byte character",
+            StoreError::NoSuchKey => "no such key",
+            StoreError::IndexOutOfRange => "index out of range",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl Error for StoreError {}
+
+/// One shard of a store: the keys that hash to it, guarded by the shard's lock.
+///
+/// Expiry is lazy: a key whose deadline has passed reads as missing even if the
+/// sweeper hasn't reached it yet. Writes remove it on the spot; reads, which only
+/// hold a shared lock, leave that to the next write or the sweeper.
+///
+/// With a key limit set, it covers every shard of the store together, counted
+/// through `data`. A key is only created once room has been claimed for it; the
+/// `Store` frees room when there is none by evicting keys from any of its shards.
+#[derive(Debug, Default)]
+pub struct Keyspace {
+    data: Entries,
+    expires: HashMap<String, SystemTime>,
+    // Most keys the keyspace may hold, None for no limit
+    limit: Option<usize>,
+    policy: EvictionPolicy,
+    // When each key was last used, as a tick of `clock`. Only kept for LRU eviction.
+    // Atomic so reads can record a use while sharing the lock. The clock is shared
+    // between the shards of a store so their ticks can be compared.
+    recency: HashMap<String, AtomicU64>,
+    clock: Arc<AtomicU64>,
+}
+
+impl Keyspace {
+    /// An empty keyspace holding at most `limit` keys, together with its siblings.
+    pub fn with_limit(limit: Option<usize>, policy: EvictionPolicy) -> Self {
+        Self {
+            limit,
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// An empty keyspace sharing this one's limit, key count and LRU clock, for
+    /// another shard of the same store.
+    pub fn sibling(&self) -> Self {
+        Self {
+            data: self.data.sibling(),
+            limit: self.limit,
+            policy: self.policy,
+            clock: Arc::clone(&self.clock),
+            ..Self::default()
+        }
+    }
+
+    /// Purges `key` if it has expired, otherwise records that it was just used.
+    fn access(&mut self, key: &str) {
+        self.purge_if_expired(key);
+        if self.policy == EvictionPolicy::AllKeysLru && self.data.contains_key(key) {
+            self.recency.entry(key.to_string()).or_default();
+            self.touch(key);
+        }
+    }
+
+    /// Records that `key` was just used. Keys first stored under a write always have
+    /// an entry, so this never needs exclusive access.
+    fn touch(&self, key: &str) {
+        if let Some(tick) = self.recency.get(key) {
+            tick.store(
+                self.clock.fetch_add(1, Ordering::Relaxed) + 1,
+                Ordering::Relaxed,
+            );
+        }
+    }
+
+    /// The value at `key` unless it is missing or expired, without purging anything.
+    fn live(&self, key: &str) -> Option<&Value> {
+        if self.is_expired(key) {
+            return None;
+        }
+        let value = self.data.get(key)?;
+        self.touch(key);
+        Some(value)
+    }
+
+    /// Drops `key` along with its expiry and recency, returning its value.
+    fn discard(&mut self, key: &str) -> Option<Value> {
+        self.expires.remove(key);
+        self.recency.remove(key);
+        self.data.remove(key)
+    }
+
+    /// Takes room for `key` if storing it would create a new key, from what
+    /// `claim_room` set aside or else from whatever is left under the limit. Fails
+    /// with `OutOfMemory` if there is none; `Store::write` evicts before that happens.
+    /// The room is held for `key`, so the caller must create it straight after.
+    fn make_room(&mut self, key: &str) -> Result<(), StoreError> {
+        let Some(limit) = self.limit else {
+            return Ok(());
+        };
+        if self.data.contains_key(key) {
+            return Ok(());
+        }
+        if self.data.reserved() == 0 && !self.data.reserve(limit) {
+            return Err(StoreError::OutOfMemory);
+        }
+
+        // The key is about to be created, which counts as its first use
+        if self.policy == EvictionPolicy::AllKeysLru {
+            self.recency.entry(key.to_string()).or_default();
+            self.touch(key);
+        }
+        Ok(())
+    }
+
+    /// Sets room aside for whichever of `keys` are missing, returning how many are
+    /// missing and how many of those got room before the store hit its limit.
+    /// Expired keys among them are purged first. Room still unused is given back
+    /// by `release_room`.
+    fn claim_room(&mut self, keys: &[&str]) -> (usize, usize) {
+        let Some(limit) = self.limit else {
+            return (0, 0);
+        };
+        let mut missing: Vec<&str> = keys.to_vec();
+        missing.sort_unstable();
+        missing.dedup();
+        missing.retain(|key| {
+            self.purge_if_expired(key);
+            !self.data.contains_key(*key)
+        });
+
+        let claimed = missing
+            .iter()
+            .take_while(|_| self.data.reserve(limit))
+            .count();
+        (missing.len(), claimed)
+    }
+
+    /// Gives back room set aside by `claim_room` that no new key took.
+    fn release_room(&mut self) {
+        self.data.release();
+    }
+
+    /// The tick at which `key` was last used, 0 if never recorded.
+    fn last_used(&self, key: &str) -> u64 {
+        self.recency
+            .get(key)
+            .map_or(0, |tick| tick.load(Ordering::Relaxed))
+    }
+
+    /// The value at `key`, first storing `empty()` there if the key is missing or
+    /// expired, or `WrongType` if it holds a different type than `empty()`. The type
+    /// is checked before making room for the key, so a refused write evicts nothing.
+    /// A caller that may leave a new container empty should drop it again with
+    /// `remove_if_empty`.
+    fn value_or_insert(
+        &mut self,
+        key: &str,
+        empty: fn() -> Value,
+    ) -> Result<&mut Value, StoreError> {
+        self.access(key);
+        let kind = std::mem::discriminant(&empty());
+        if let Some(value) = self.data.get(key) {
+            if std::mem::discriminant(value) != kind {
+                return Err(StoreError::WrongType);
+            }
+            return Ok(self.data.get_mut(key).expect("key was just read"));
+        }
+        self.make_room(key)?;
+        Ok(self.data.get_or_insert_with(key, empty))
+    }
+
+    fn is_expired(&self, key: &str) -> bool {
+        self.expires
+            .get(key)
+            .is_some_and(|deadline| *deadline <= SystemTime::now())
+    }
+
+    fn purge_if_expired(&mut self, key: &str) {
+        if self.is_expired(key) {
+            self.discard(key);
+        }
+    }
+
+    /// The value at `key`, whatever its type.
+    pub fn get_value(&self, key: &str) -> Option<&Value> {
+        self.live(key)
+    }
+
+    /// The string at `key`, or `WrongType` if the key holds another kind of value.
+    pub fn get(&self, key: &str) -> Result<Option<&String>, StoreError> {
+        match self.get_value(key) {
+            Some(Value::Str(value)) => Ok(Some(value)),
+            Some(_) => Err(StoreError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// Stores `value` under `key`, replacing any existing value regardless of its type.
+    /// Clears any expiry and returns the value it replaced.
+    pub fn set(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Result<Option<Value>, StoreError> {
+        let key = key.into();
+        self.purge_if_expired(&key);
+        self.make_room(&key)?;
+        self.expires.remove(&key);
+        let previous = self.data.insert(key.clone(), value.into());
+        self.access(&key);
+        Ok(previous)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.purge_if_expired(key);
+        self.discard(key)
+    }
+
+    /// Moves the value and expiry at `source` to `destination`, replacing whatever
+    /// was there. Returns false if `source` doesn't exist.
+    pub fn rename(&mut self, source: &str, destination: &str) -> bool {
+        self.purge_if_expired(destination);
+        // Renaming a key to itself takes it out and puts it straight back
+        let Some(moved) = self.detach(source) else {
+            return false;
+        };
+        self.attach(destination, moved);
+        true
+    }
+
+    /// Takes `key` out along with its expiry. It keeps its place in the key count,
+    /// which `attach` hands on to the key it moves to.
+    fn detach(&mut self, key: &str) -> Option<(Value, Option<SystemTime>)> {
+        self.purge_if_expired(key);
+        let value = self.data.detach(key)?;
+        self.recency.remove(key);
+        Some((value, self.expires.remove(key)))
+    }
+
+    /// Stores a value and expiry taken out by `detach` under `key`, replacing
+    /// whatever was there.
+    fn attach(&mut self, key: &str, (value, deadline): (Value, Option<SystemTime>)) {
+        self.discard(key);
+        if let Some(deadline) = deadline {
+            self.expires.insert(key.to_string(), deadline);
+        }
+        self.data.attach(key.to_string(), value);
+        self.access(key);
+    }
+
+    /// Whether `key` is live. Unlike reading it, this doesn't count as a use.
+    pub fn contains(&self, key: &str) -> bool {
+        !self.is_expired(key) && self.data.contains_key(key)
+    }
+
+    /// Records a use of `key` without reading it, returning whether it is live.
+    pub fn touch_key(&self, key: &str) -> bool {
+        self.live(key).is_some()
+    }
+
+    /// Sets the deadline after which `key` is treated as missing.
+    /// Returns false if the key doesn't exist.
+    pub fn expire_at(&mut self, key: &str, deadline: SystemTime) -> bool {
+        if !self.contains(key) {
+            return false;
+        }
+
+        self.expires.insert(key.to_string(), deadline);
+        true
+    }
+
+    /// Removes any deadline on `key`, leaving its value alone.
+    /// Returns false if the key doesn't exist or had no deadline.
+    pub fn persist(&mut self, key: &str) -> bool {
+        self.purge_if_expired(key);
+        self.expires.remove(key).is_some()
+    }
+
+    pub fn ttl(&self, key: &str) -> Ttl {
+        if !self.contains(key) {
+            return Ttl::Missing;
+        }
+
+        match self.expires.get(key) {
+            Some(deadline) => Ttl::Expires(time::duration_between(SystemTime::now(), *deadline)),
+            None => Ttl::Persistent,
+        }
+    }
+
+    /// Appends `suffix` to the string at `key`, creating it if missing.
+    /// Returns the new length in bytes.
+    pub fn append(&mut self, key: &str, suffix: &str) -> Result<usize, StoreError> {
+        let value = self.get_str_mut(key)?;
+        value.push_str(suffix);
+        Ok(value.len())
+    }
+
+    /// The bytes of the string at `key` between the inclusive offsets `start` and
+    /// `stop`, indexed like `range`. A range that splits a multi-byte character
+    /// replaces the broken pieces with U+FFFD.
+    pub fn getrange(&self, key: &str, start: i64, stop: i64) -> Result<String, StoreError> {
+        let Some(value) = self.get(key)? else {
+            return Ok(String::new());
+        };
+        let Some(range) = clamp_range(value.len(), start, stop) else {
+            return Ok(String::new());
+        };
+        Ok(String::from_utf8_lossy(&value.as_bytes()[range]).into_owned())
+    }
+
+    /// Overwrites the string at `key` with `patch` starting `offset` bytes in, padding
+    /// with zero bytes if the string is shorter, and returns its new length. A missing
+    /// key is treated as an empty string, though an empty `patch` doesn't create it.
+    pub fn setrange(&mut self, key: &str, offset: usize, patch: &str) -> Result<usize, StoreError> {
+        self.access(key);
+        let current = self.get(key)?.map_or(0, String::len);
+        if patch.is_empty() {
+            return Ok(current);
+        }
+        let value = self.get_str_mut(key)?;
+
+        // Overwriting part of a multi-byte character would leave text that isn't UTF-8
+        let end = offset + patch.len();
+        let len = value.len();
+        if !value.is_char_boundary(offset.min(len)) || !value.is_char_boundary(end.min(len)) {
+            return Err(StoreError::InvalidUtf8);
+        }
+
+        if len < offset {
+            value.extend(std::iter::repeat_n('\0', offset - len));
+        }
+        value.replace_range(offset..end.min(value.len()), patch);
+        Ok(value.len())
+    }
+
+    /// The string at `key` for changing in place, created empty if missing, or
+    /// `WrongType` if the key holds another kind of value.
+    pub fn get_str_mut(&mut self, key: &str) -> Result<&mut String, StoreError> {
+        match self.value_or_insert(key, || Value::Str(String::new()))? {
+            Value::Str(value) => Ok(value),
+            _ => Err(StoreError::WrongType),
+        }
+    }
+
+    /// Adds `delta` to the integer stored at `key`, treating a missing key as 0.
+    /// The key keeps its expiry. Returns the new value.
+    pub fn incr_by(&mut self, key: &str, delta: i64) -> Result<i64, StoreError> {
+        // An expired key must go along with its deadline before it is recreated
+        self.access(key);
+        let updated = add_to_integer(self.get(key)?.map(String::as_str), delta)?;
+        self.make_room(key)?;
+        self.data
+            .insert(key.to_string(), Value::Str(updated.to_string()));
+        Ok(updated)
+    }
+
+    /// Drops `key` if it holds a container that has become empty.
+    fn remove_if_empty(&mut self, key: &str) {
+        if self.data.get(key).is_some_and(Value::is_empty_container) {
+            self.discard(key);
+        }
+    }
+
+    /// The list at `key`, or `WrongType` if the key holds another kind of value.
+    pub fn list(&self, key: &str) -> Result<Option<&VecDeque<String>>, StoreError> {
+        match self.live(key) {
+            Some(Value::List(list)) => Ok(Some(list)),
+            Some(_) => Err(StoreError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// The list at `key` for changing in place, or `WrongType` if the key holds
+    /// another kind of value.
+    pub fn list_mut(&mut self, key: &str) -> Result<Option<&mut VecDeque<String>>, StoreError> {
+        self.access(key);
+        match self.data.get_mut(key) {
+            Some(Value::List(list)) => Ok(Some(list)),
+            Some(_) => Err(StoreError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// The list at `key` for changing in place, created empty if missing, or
+    /// `WrongType` if the key holds another kind of value.
+    pub fn get_list_mut(&mut self, key: &str) -> Result<&mut VecDeque<String>, StoreError> {
+        match self.value_or_insert(key, || Value::List(VecDeque::new()))? {
+            Value::List(list) => Ok(list),
+            _ => Err(StoreError::WrongType),
+        }
+    }
+
+    /// Pushes `values` one at a time onto `end` of the list at `key`, creating it if
+    /// missing. Returns the new length.
+    pub fn push(
+        &mut self,
+        key: &str,
+        values: &[String],
+        end: ListEnd,
+    ) -> Result<usize, StoreError> {
+        let list = self.get_list_mut(key)?;
+
+        for value in values {
+            match end {
+                ListEnd::Left => list.push_front(value.clone()),
+                ListEnd::Right => list.push_back(value.clone()),
+            }
+        }
+
+        Ok(list.len())
+    }
+
+    /// Pops up to `count` elements from `end` of the list at `key`, removing the key
+    /// once the list is empty. Returns `None` if the key is missing.
+    pub fn pop(
+        &mut self,
+        key: &str,
+        count: usize,
+        end: ListEnd,
+    ) -> Result<Option<Vec<String>>, StoreError> {
+        let Some(list) = self.list_mut(key)? else {
+            return Ok(None);
+        };
+
+        let mut popped = Vec::new();
+        while popped.len() < count {
+            let next = match end {
+                ListEnd::Left => list.pop_front(),
+                ListEnd::Right => list.pop_back(),
+            };
+            match next {
+                Some(value) => popped.push(value),
+                None => break,
+            }
+        }
+
+        self.remove_if_empty(key);
+        Ok(Some(popped))
+    }
+
+    /// Elements of the list at `key` between the inclusive indices `start` and `stop`.
+    /// Negative indices count from the end and out-of-range indices are clamped, so a
+    /// missing key or an empty range yields an empty list.
+    pub fn range(&self, key: &str, start: i64, stop: i64) -> Result<Vec<String>, StoreError> {
+        let Some(list) = self.list(key)? else {
+            return Ok(Vec::new());
+        };
+
+        let Some(range) = clamp_range(list.len(), start, stop) else {
+            return Ok(Vec::new());
+        };
+        Ok(list.range(range).cloned().collect())
+    }
+
+    /// The element at `index` of the list at `key`, where a negative index counts from
+    /// the end. None if the key is missing or the index is out of range.
+    pub fn lindex(&self, key: &str, index: i64) -> Result<Option<String>, StoreError> {
+        let Some(list) = self.list(key)? else {
+            return Ok(None);
+        };
+        Ok(resolve_index(list.len(), index).and_then(|index| list.get(index).cloned()))
+    }
+
+    /// Replaces the element at `index` of the list at `key`, indexed like `lindex`.
+    /// Fails if the key is missing or the index is out of range.
+    pub fn lset(&mut self, key: &str, index: i64, value: &str) -> Result<(), StoreError> {
+        let list = self.list_mut(key)?.ok_or(StoreError::NoSuchKey)?;
+        let index = resolve_index(list.len(), index).ok_or(StoreError::IndexOutOfRange)?;
+        list[index] = value.to_string();
+        Ok(())
+    }
+
+    /// The hash at `key`, or `WrongType` if the key holds another kind of value.
+    pub fn hash(&self, key: &str) -> Result<Option<&HashMap<String, String>>, StoreError> {
+        match self.live(key) {
+            Some(Value::Hash(hash)) => Ok(Some(hash)),
+            Some(_) => Err(StoreError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// The hash at `key` for changing in place, or `WrongType` if the key holds
+    /// another kind of value.
+    pub fn hash_mut(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<&mut HashMap<String, String>>, StoreError> {
+        self.access(key);
+        match self.data.get_mut(key) {
+            Some(Value::Hash(hash)) => Ok(Some(hash)),
+            Some(_) => Err(StoreError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// The hash at `key` for changing in place, created empty if missing, or
+    /// `WrongType` if the key holds another kind of value.
+    pub fn get_hash_mut(&mut self, key: &str) -> Result<&mut HashMap<String, String>, StoreError> {
+        match self.value_or_insert(key, || Value::Hash(HashMap::new()))? {
+            Value::Hash(hash) => Ok(hash),
+            _ => Err(StoreError::WrongType),
+        }
+    }
+
+    /// Sets each field/value pair in the hash at `key`, creating it if missing.
+    /// Returns how many fields were newly created rather than updated.
+    pub fn hset(&mut self, key: &str, pairs: &[(String, String)]) -> Result<usize, StoreError> {
+        let hash = self.get_hash_mut(key)?;
+        let created = pairs
+            .iter()
+            .filter(|(field, value)| hash.insert(field.clone(), value.clone()).is_none())
+            .count();
+
+        Ok(created)
+    }
+
+    pub fn hget(&self, key: &str, field: &str) -> Result<Option<String>, StoreError> {
+        Ok(self.hash(key)?.and_then(|hash| hash.get(field).cloned()))
+    }
+
+    /// Adds `delta` to the integer in `field` of the hash at `key`, treating a missing
+    /// key or field as 0, and returns the new value.
+    pub fn hincr_by(&mut self, key: &str, field: &str, delta: i64) -> Result<i64, StoreError> {
+        self.access(key);
+        let current = self.hash(key)?.and_then(|hash| hash.get(field));
+        let updated = add_to_integer(current.map(String::as_str), delta)?;
+        self.get_hash_mut(key)?
+            .insert(field.to_string(), updated.to_string());
+        Ok(updated)
+    }
+
+    /// Removes `fields` from the hash at `key`, dropping the key once the hash is empty.
+    /// Returns how many fields were removed.
+    pub fn hdel(&mut self, key: &str, fields: &[String]) -> Result<usize, StoreError> {
+        let Some(hash) = self.hash_mut(key)? else {
+            return Ok(0);
+        };
+
+        let removed = fields
+            .iter()
+            .filter(|field| hash.remove(field.as_str()).is_some())
+            .count();
+
+        self.remove_if_empty(key);
+        Ok(removed)
+    }
+
+    /// Every field/value pair in the hash at `key`, sorted by field.
+    pub fn hgetall(&self, key: &str) -> Result<Vec<(String, String)>, StoreError> {
+        let mut pairs: Vec<(String, String)> = self
+            .hash(key)?
+            .map(|hash| {
+                hash.iter()
+                    .map(|(field, value)| (field.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        pairs.sort();
+        Ok(pairs)
+    }
+
+    /// The set at `key`, or `WrongType` if the key holds another kind of value.
+    pub fn members(&self, key: &str) -> Result<Option<&HashSet<String>>, StoreError> {
+        match self.live(key) {
+            Some(Value::Set(set)) => Ok(Some(set)),
+            Some(_) => Err(StoreError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// The set at `key` for changing in place, or `WrongType` if the key holds
+    /// another kind of value.
+    pub fn set_mut(&mut self, key: &str) -> Result<Option<&mut HashSet<String>>, StoreError> {
+        self.access(key);
+        match self.data.get_mut(key) {
+            Some(Value::Set(set)) => Ok(Some(set)),
+            Some(_) => Err(StoreError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// The set at `key` for changing in place, created empty if missing, or
+    /// `WrongType` if the key holds another kind of value.
+    pub fn get_set_mut(&mut self, key: &str) -> Result<&mut HashSet<String>, StoreError> {
+        match self.value_or_insert(key, || Value::Set(HashSet::new()))? {
+            Value::Set(set) => Ok(set),
+            _ => Err(StoreError::WrongType),
+        }
+    }
+
+    /// Adds `members` to the set at `key`, creating it if missing.
+    /// Returns how many were not already present.
+    pub fn sadd(&mut self, key: &str, members: &[String]) -> Result<usize, StoreError> {
+        let set = self.get_set_mut(key)?;
+        Ok(members
+            .iter()
+            .filter(|member| set.insert(member.to_string()))
+            .count())
+    }
+
+    /// Removes `members` from the set at `key`, dropping the key once the set is empty.
+    /// Returns how many were removed.
+    pub fn srem(&mut self, key: &str, members: &[String]) -> Result<usize, StoreError> {
+        let Some(set) = self.set_mut(key)? else {
+            return Ok(0);
+        };
+
+        let removed = members
+            .iter()
+            .filter(|member| set.remove(member.as_str()))
+            .count();
+
+        self.remove_if_empty(key);
+        Ok(removed)
+    }
+
+    /// Members of the set at `key`, sorted so replies are stable.
+    pub fn smembers(&self, key: &str) -> Result<Vec<String>, StoreError> {
+        let mut members: Vec<String> = self
+            .members(key)?
+            .map(|set| set.iter().cloned().collect())
+            .unwrap_or_default();
+
+        members.sort();
+        Ok(members)
+    }
+
+    pub fn sismember(&self, key: &str, member: &str) -> Result<bool, StoreError> {
+        Ok(self.members(key)?.is_some_and(|set| set.contains(member)))
+    }
+
+    /// All live keys matching the glob `pattern`. This walks the whole keyspace.
+    pub fn keys(&self, pattern: &str) -> Vec<String> {
+        self.data
+            .keys()
+            .filter(|key| !self.is_expired(key) && glob_match(pattern, key))
+            .cloned()
+            .collect()
+    }
+
+    /// Number of live keys. Expired keys the sweeper hasn't reached yet are not counted.
+    pub fn size(&self) -> usize {
+        self.data.keys().filter(|key| !self.is_expired(key)).count()
+    }
+
+    /// Number of live keys that have an expiry set.
+    pub fn expiring(&self) -> usize {
+        self.expires
+            .keys()
+            .filter(|key| !self.is_expired(key))
+            .count()
+    }
+
+    /// Removes every key along with its expiry. The key limit stays in place.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.expires.clear();
+        self.recency.clear();
+    }
+
+    /// Removes every key whose deadline has passed, returning how many were dropped.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = SystemTime::now();
+        let expired: Vec<String> = self
+            .expires
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &expired {
+            self.discard(key);
+        }
+
+        expired.len()
+    }
+}
+
+/// Guards over the shards a set of keys hash to, taken in shard order so that two
+/// clients locking overlapping keys can't deadlock.
+pub struct Shards<G> {
+    guards: Vec<(usize, G)>,
+    count: usize,
+}
+
+impl<G: Deref<Target = Keyspace>> Shards<G> {
+    /// The shard holding `key`, which must be one of the keys the guards were taken for.
+    pub fn get(&self, key: &str) -> &Keyspace {
+        let index = shard_index(key, self.count);
+        let (_, guard) = self
+            .guards
+            .iter()
+            .find(|(locked, _)| *locked == index)
+            .expect("key was not locked");
+        guard
+    }
+
+    /// Every locked shard, in shard order.
+    pub fn iter(&self) -> impl Iterator<Item = &Keyspace> {
+        self.guards.iter().map(|(_, guard)| &**guard)
+    }
+}
+
+impl<G: DerefMut<Target = Keyspace>> Shards<G> {
+    /// The shard holding `key` for changing, which must be one of the keys the
+    /// guards were taken for.
+    pub fn get_mut(&mut self, key: &str) -> &mut Keyspace {
+        let index = shard_index(key, self.count);
+        let (_, guard) = self
+            .guards
+            .iter_mut()
+            .find(|(locked, _)| *locked == index)
+            .expect("key was not locked");
+        guard
+    }
+
+    /// Like `Keyspace::claim_room`, for keys spread over the locked shards.
+    fn claim_room(&mut self, keys: &[&str]) -> (usize, usize) {
+        let count = self.count;
+        self.guards
+            .iter_mut()
+            .map(|(index, guard)| {
+                let local: Vec<&str> = keys
+                    .iter()
+                    .copied()
+                    .filter(|key| shard_index(key, count) == *index)
+                    .collect();
+                guard.claim_room(&local)
+            })
+            .fold((0, 0), |(missing, claimed), (more, got)| {
+                (missing + more, claimed + got)
+            })
+    }
+
+    fn release_room(&mut self) {
+        for (_, guard) in &mut self.guards {
+            guard.release_room();
+        }
+    }
+}
+
+/// `current` parsed as an integer (0 if missing) plus `delta`, as INCRBY and HINCRBY
+/// compute it.
+fn add_to_integer(current: Option<&str>, delta: i64) -> Result<i64, StoreError> {
+    let current = match current {
+        Some(value) => value.parse::<i64>().map_err(|_| StoreError::NotAnInteger)?,
+        None => 0,
+    };
+    current.checked_add(delta).ok_or(StoreError::Overflow)
+}
+
+/// The position in a sequence of `len` items that `index` refers to, where negative
+/// indices count from the end. None if it is out of range.
+fn resolve_index(len: usize, index: i64) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    usize::try_from(index).ok().filter(|index| *index < len)
+}
+
+/// The indices of a sequence of `len` items between the inclusive bounds `start` and
+/// `stop`, where negative bounds count from the end and out-of-range ones are
+/// clamped. None if that leaves nothing.
+fn clamp_range(len: usize, start: i64, stop: i64) -> Option<RangeInclusive<usize>> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop || start >= len {
+        return None;
+    }
+    Some(start as usize..=stop as usize)
+}
+
+// Which of `count` shards `key` lives in
+fn shard_index(key: &str, count: usize) -> usize {
+    (key_hash(key) % count as u64) as usize
+}
+
+// Stable hash of `key` for the life of the process
+fn key_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Bits of a SCAN cursor holding the position within a shard; the shard index sits
+// above them, and is kept below 2^15 so cursors fit a signed integer
+const POSITION_BITS: u32 = 48;
+const MAX_SHARDS: usize = 1 << 15;
+
+// Where SCAN places `key` within its shard
+fn scan_position(key: &str) -> u64 {
+    key_hash(key) >> (u64::BITS - POSITION_BITS)
+}
+
+impl Store {
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARDS, None, EvictionPolicy::NoEviction)
+    }
+
+    /// A store split into `shards` independently locked parts, holding at most
+    /// `limit` keys and making room for new ones by `policy`. The shards share one
+    /// count of their keys, so the limit applies to the store as a whole.
+    /// At most 32768 shards are used.
+    pub fn with_shards(shards: usize, limit: Option<usize>, policy: EvictionPolicy) -> Self {
+        let first = Keyspace::with_limit(limit, policy);
+        let siblings: Vec<Keyspace> = (1..shards.clamp(1, MAX_SHARDS))
+            .map(|_| first.sibling())
+            .collect();
+        Self {
+            shards: std::iter::once(first)
+                .chain(siblings)
+                .map(RwLock::new)
+                .collect(),
+            on_evict: OnceLock::new(),
+        }
+    }
+
+    /// Calls `hook` with every key evicted to make room from now on, while the
+    /// store is still locked. Only the first hook given is kept.
+    pub fn on_evict(&self, hook: impl Fn(&str) + Send + Sync + 'static) {
+        let _ = self.on_evict.set(EvictionHook(Arc::new(hook)));
+    }
+
+    fn shard(&self, key: &str) -> &RwLock<Keyspace> {
+        &self.shards[shard_index(key, self.shards.len())]
+    }
+
+    /// Exclusive access to the shard holding `key`, for changing it.
+    pub fn lock(&self, key: &str) -> RwLockWriteGuard<'_, Keyspace> {
+        self.shard(key).write_unpoisoned()
+    }
+
+    /// Shared access to the shard holding `key`, for reading alongside other readers.
+    pub fn read(&self, key: &str) -> RwLockReadGuard<'_, Keyspace> {
+        self.shard(key).read_unpoisoned()
+    }
+
+    // Shard indices covering `keys`, each once and in ascending order
+    fn shard_indices<'k>(&self, keys: impl IntoIterator<Item = &'k str>) -> Vec<usize> {
+        let mut indices: Vec<usize> = keys
+            .into_iter()
+            .map(|key| shard_index(key, self.shards.len()))
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Exclusive access to every shard any of `keys` live in.
+    pub fn lock_keys<'k>(
+        &self,
+        keys: impl IntoIterator<Item = &'k str>,
+    ) -> Shards<RwLockWriteGuard<'_, Keyspace>> {
+        Shards {
+            guards: self
+                .shard_indices(keys)
+                .into_iter()
+                .map(|index| (index, self.shards[index].write_unpoisoned()))
+                .collect(),
+            count: self.shards.len(),
+        }
+    }
+
+    /// Shared access to every shard any of `keys` live in.
+    pub fn read_keys<'k>(
+        &self,
+        keys: impl IntoIterator<Item = &'k str>,
+    ) -> Shards<RwLockReadGuard<'_, Keyspace>> {
+        Shards {
+            guards: self
+                .shard_indices(keys)
+                .into_iter()
+                .map(|index| (index, self.shards[index].read_unpoisoned()))
+                .collect(),
+            count: self.shards.len(),
+        }
+    }
+
+    /// Shared access to every shard at once, for a consistent view of the whole store.
+    pub fn read_all(&self) -> Shards<RwLockReadGuard<'_, Keyspace>> {
+        Shards {
+            guards: self
+                .shards
+                .iter()
+                .enumerate()
+                .map(|(index, shard)| (index, shard.read_unpoisoned()))
+                .collect(),
+            count: self.shards.len(),
+        }
+    }
+
+    /// Runs `write` on the shard holding `key`, with room set aside first in case it
+    /// creates the key. When the store is full, the shard is let go while keys are
+    /// evicted from whichever shards the policy picks, then taken again. Fails with
+    /// `OutOfMemory` if the policy won't evict or nothing is left to give up.
+    pub fn write<R>(
+        &self,
+        key: &str,
+        write: impl FnOnce(&mut Keyspace) -> Result<R, StoreError>,
+    ) -> Result<R, StoreError> {
+        let mut data = self.lock(key);
+        loop {
+            let (missing, claimed) = data.claim_room(&[key]);
+            if claimed == missing {
+                break;
+            }
+            data.release_room();
+            drop(data);
+            self.evict(missing)?;
+            data = self.lock(key);
+        }
+        let result = write(&mut data);
+        data.release_room();
+        result
+    }
+
+    /// Like `write`, for a write to several `keys` at once that may create any of
+    /// `created`. Room is set aside for all of those before `write` runs, so it never
+    /// stops part-way for lack of room.
+    pub fn write_keys<R>(
+        &self,
+        keys: &[&str],
+        created: &[&str],
+        write: impl FnOnce(&mut Shards<RwLockWriteGuard<'_, Keyspace>>) -> Result<R, StoreError>,
+    ) -> Result<R, StoreError> {
+        let mut shards = self.lock_keys(keys.iter().copied());
+        loop {
+            let (missing, claimed) = shards.claim_room(created);
+            if claimed == missing {
+                break;
+            }
+            shards.release_room();
+            drop(shards);
+            self.evict(missing)?;
+            shards = self.lock_keys(keys.iter().copied());
+        }
+        let result = write(&mut shards);
+        shards.release_room();
+        result
+    }
+
+    /// Frees room for `needed` more keys. Expired keys go first, then keys chosen by
+    /// the eviction policy from across every shard: the least recently used one, or
+    /// one picked uniformly at random. Every shard is locked, in order, meanwhile.
+    fn evict(&self, needed: usize) -> Result<(), StoreError> {
+        let mut shards: Vec<RwLockWriteGuard<'_, Keyspace>> = self
+            .shards
+            .iter()
+            .map(|shard| shard.write_unpoisoned())
+            .collect();
+        let (Some(limit), policy) = (shards[0].limit, shards[0].policy) else {
+            return Ok(());
+        };
+
+        for shard in &mut shards {
+            shard.purge_expired();
+        }
+        while shards[0].data.total() + needed > limit {
+            let victim = match policy {
+                EvictionPolicy::NoEviction => None,
+                EvictionPolicy::AllKeysLru => shards
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(index, shard)| {
+                        shard
+                            .data
+                            .keys()
+                            .map(move |key| (shard.last_used(key), index, key))
+                    })
+                    .min()
+                    .map(|(_, index, key)| (index, key.clone())),
+                EvictionPolicy::AllKeysRandom => {
+                    let len = shards.iter().map(|shard| shard.data.len()).sum();
+                    (len > 0).then(|| {
+                        let mut position = eviction::random_index(len);
+                        let index = shards
+                            .iter()
+                            .position(|shard| {
+                                let here = position < shard.data.len();
+                                if !here {
+                                    position -= shard.data.len();
+                                }
+                                here
+                            })
+                            .expect("position is below the total");
+                        let key = shards[index].data.keys().nth(position).cloned();
+                        (index, key.expect("position is within the shard"))
+                    })
+                }
+            };
+            let Some((index, key)) = victim else {
+                return Err(StoreError::OutOfMemory);
+            };
+            shards[index].discard(&key);
+            if let Some(hook) = self.on_evict.get() {
+                (hook.0)(&key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `f` with the whole store to itself. The data is moved into a private
+    /// store for the duration while this one stays locked, so `f` can make any
+    /// number of calls and no other client sees or changes anything in between.
+    pub fn exclusive<R>(&self, f: impl FnOnce(&Store) -> R) -> R {
+        // Every shard, taken in order like any other multi-shard lock
+        let mut guards: Vec<RwLockWriteGuard<'_, Keyspace>> = self
+            .shards
+            .iter()
+            .map(|shard| shard.write_unpoisoned())
+            .collect();
+        let lent = Lent {
+            store: Store {
+                shards: guards
+                    .iter_mut()
+                    .map(|keyspace| RwLock::new(std::mem::take(&mut **keyspace)))
+                    .collect(),
+                on_evict: self.on_evict.clone(),
+            },
+            guards,
+        };
+
+        f(&lent.store)
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>, StoreError> {
+        self.read(key).get(key).map(|value| value.cloned())
+    }
+
+    /// Name of the type stored at `key`, or `none` if it is missing.
+    pub fn type_of(&self, key: &str) -> &'static str {
+        self.read(key)
+            .get_value(key)
+            .map(Value::type_name)
+            .unwrap_or("none")
+    }
+
+    /// How the value at `key` would be encoded by Redis, or None if it is missing.
+    pub fn encoding_of(&self, key: &str) -> Option<&'static str> {
+        self.read(key).get_value(key).map(Value::encoding)
+    }
+
+    /// Stores `value` under `key`, returning the value it replaced, if any.
+    pub fn set(
+        &self,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Result<Option<Value>, StoreError> {
+        let key = key.into();
+        self.write(&key, |data| data.set(key.as_str(), value))
+    }
+
+    pub fn remove(&self, key: &str) -> Option<Value> {
+        self.lock(key).remove(key)
+    }
+
+    /// Moves the value and expiry at `source` to `destination`, even when the two
+    /// live in different shards. Returns false if `source` doesn't exist.
+    pub fn rename(&self, source: &str, destination: &str) -> bool {
+        let mut shards = self.lock_keys([source, destination]);
+        if shard_index(source, self.shards.len()) == shard_index(destination, self.shards.len()) {
+            return shards.get_mut(source).rename(source, destination);
+        }
+
+        let Some(moved) = shards.get_mut(source).detach(source) else {
+            return false;
+        };
+        // The key keeps its place in the count, so the limit can't be passed
+        shards.get_mut(destination).attach(destination, moved);
+        true
+    }
+
+    /// Copies the value and expiry at `source` to `destination`, returning false if
+    /// there was nothing to copy or `destination` exists and `replace` isn't set.
+    /// The two keys must differ.
+    pub fn copy(&self, source: &str, destination: &str, replace: bool) -> Result<bool, StoreError> {
+        self.write_keys(&[source, destination], &[destination], |shards| {
+            let origin = shards.get_mut(source);
+            origin.access(source);
+            let Some(value) = origin.data.get(source).cloned() else {
+                return Ok(false);
+            };
+            let deadline = origin.expires.get(source).copied();
+
+            // A replaced value is overwritten in place, so its room is never let go
+            let target = shards.get_mut(destination);
+            target.purge_if_expired(destination);
+            if target.data.contains_key(destination) {
+                if !replace {
+                    return Ok(false);
+                }
+                target.expires.remove(destination);
+            } else {
+                target.make_room(destination)?;
+            }
+            if let Some(deadline) = deadline {
+                target.expires.insert(destination.to_string(), deadline);
+            }
+            target.data.insert(destination.to_string(), value);
+            target.access(destination);
+            Ok(true)
+        })
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.read(key).contains(key)
+    }
+
+    pub fn expire_at(&self, key: &str, deadline: SystemTime) -> bool {
+        self.lock(key).expire_at(key, deadline)
+    }
+
+    pub fn persist(&self, key: &str) -> bool {
+        self.lock(key).persist(key)
+    }
+
+    pub fn ttl(&self, key: &str) -> Ttl {
+        self.read(key).ttl(key)
+    }
+
+    /// All live keys matching `pattern`, from every shard.
+    pub fn keys(&self, pattern: &str) -> Vec<String> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.read_unpoisoned().keys(pattern))
+            .collect()
+    }
+
+    /// A live key chosen uniformly at random, or None if there are none.
+    ///
+    /// Maps have no random access, so this does one pass of reservoir sampling over
+    /// every shard in turn: the n-th live key seen replaces the pick with chance 1/n,
+    /// which leaves each of them equally likely at the end. Only one shard is locked
+    /// at a time, so keys written elsewhere during the pass may or may not be seen.
+    pub fn random_key(&self) -> Option<String> {
+        let mut seen = 0;
+        let mut pick = None;
+        for shard in self.shards.iter() {
+            let keyspace = shard.read_unpoisoned();
+            for key in keyspace.data.keys() {
+                if keyspace.is_expired(key) {
+                    continue;
+                }
+                seen += 1;
+                if eviction::random_index(seen) == 0 {
+                    pick = Some(key.clone());
+                }
+            }
+        }
+        pick
+    }
+
+    /// One page of an incremental walk over every key, returning the cursor to pass
+    /// next (0 once the walk is done) and at least `count` keys while any remain.
+    ///
+    /// The walk goes one shard at a time, visiting each shard's keys in order of a
+    /// fixed hash. The cursor holds the shard and the hash to carry on from, so keys
+    /// added or removed between pages don't shift the others: a key present for the
+    /// whole walk is returned exactly once. Keys sharing a hash are always returned on
+    /// the same page. A page costs about `count` keys' worth of work however large
+    /// the store is, and each shard is only locked while it is read.
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<String>) {
+        let count = count.max(1);
+        let mut shard = usize::try_from(cursor >> POSITION_BITS).unwrap_or(usize::MAX);
+        let mut from = cursor & ((1 << POSITION_BITS) - 1);
+        let mut keys = Vec::new();
+
+        while shard < self.shards.len() {
+            let keyspace = self.shards[shard].read_unpoisoned();
+            let (page, next) = keyspace
+                .data
+                .page(from, count - keys.len(), |key| !keyspace.is_expired(key));
+            keys.extend(page);
+            if let Some(next) = next {
+                return (((shard as u64) << POSITION_BITS) | next, keys);
+            }
+
+            shard += 1;
+            from = 0;
+            if keys.len() >= count {
+                break;
+            }
+        }
+
+        let next = if shard < self.shards.len() {
+            (shard as u64) << POSITION_BITS
+        } else {
+            0
+        };
+        (next, keys)
+    }
+
+    pub fn size(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read_unpoisoned().size())
+            .sum()
+    }
+
+    /// Number of live keys that have an expiry set, across every shard.
+    pub fn expiring(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read_unpoisoned().expiring())
+            .sum()
+    }
+
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.write_unpoisoned().clear();
+        }
+    }
+
+    /// Removes every expired key from every shard, returning how many were dropped.
+    pub fn purge_expired(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.write_unpoisoned().purge_expired())
+            .sum()
+    }
+
+    pub fn append(&self, key: &str, suffix: &str) -> Result<usize, StoreError> {
+        self.write(key, |data| data.append(key, suffix))
+    }
+
+    pub fn getrange(&self, key: &str, start: i64, stop: i64) -> Result<String, StoreError> {
+        self.read(key).getrange(key, start, stop)
+    }
+
+    pub fn setrange(&self, key: &str, offset: usize, patch: &str) -> Result<usize, StoreError> {
+        self.write(key, |data| data.setrange(key, offset, patch))
+    }
+
+    pub fn incr_by(&self, key: &str, delta: i64) -> Result<i64, StoreError> {
+        self.write(key, |data| data.incr_by(key, delta))
+    }
+
+    pub fn push(&self, key: &str, values: &[String], end: ListEnd) -> Result<usize, StoreError> {
+        self.write(key, |data| data.push(key, values, end))
+    }
+
+    pub fn hset(&self, key: &str, pairs: &[(String, String)]) -> Result<usize, StoreError> {
+        self.write(key, |data| data.hset(key, pairs))
+    }
+
+    pub fn hget(&self, key: &str, field: &str) -> Result<Option<String>, StoreError> {
+        self.read(key).hget(key, field)
+    }
+
+    pub fn hincr_by(&self, key: &str, field: &str, delta: i64) -> Result<i64, StoreError> {
+        self.write(key, |data| data.hincr_by(key, field, delta))
+    }
+
+    pub fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, StoreError> {
+        self.lock(key).hdel(key, fields)
+    }
+
+    pub fn hgetall(&self, key: &str) -> Result<Vec<(String, String)>, StoreError> {
+        self.read(key).hgetall(key)
+    }
+
+    pub fn sadd(&self, key: &str, members: &[String]) -> Result<usize, StoreError> {
+        self.write(key, |data| data.sadd(key, members))
+    }
+
+    pub fn srem(&self, key: &str, members: &[String]) -> Result<usize, StoreError> {
+        self.lock(key).srem(key, members)
+    }
+
+    pub fn smembers(&self, key: &str) -> Result<Vec<String>, StoreError> {
+        self.read(key).smembers(key)
+    }
+
+    /// Combines the sets at `keys` with `operation`, treating missing keys as empty
+    /// sets, and returns the result sorted. All the keys are read under one set of
+    /// locks, and any of them holding another type fails the whole operation.
+    pub fn combine_sets(
+        &self,
+        keys: &[String],
+        operation: SetOperation,
+    ) -> Result<Vec<String>, StoreError> {
+        let data = self.read_keys(keys.iter().map(String::as_str));
+        let empty = HashSet::new();
+        let sets = keys
+            .iter()
+            .map(|key| Ok(data.get(key).members(key)?.unwrap_or(&empty)))
+            .collect::<Result<Vec<&HashSet<String>>, StoreError>>()?;
+
+        let Some((first, rest)) = sets.split_first() else {
+            return Ok(Vec::new());
+        };
+        let mut members: Vec<String> = match operation {
+            SetOperation::Intersection => first
+                .iter()
+                .filter(|member| rest.iter().all(|set| set.contains(*member)))
+                .cloned()
+                .collect(),
+            SetOperation::Union => sets
+                .iter()
+                .flat_map(|set| set.iter())
+                .collect::<HashSet<&String>>()
+                .into_iter()
+                .cloned()
+                .collect(),
+            SetOperation::Difference => first
+                .iter()
+                .filter(|member| !rest.iter().any(|set| set.contains(*member)))
+                .cloned()
+                .collect(),
+        };
+        members.sort_unstable();
+        Ok(members)
+    }
+
+    pub fn sismember(&self, key: &str, member: &str) -> Result<bool, StoreError> {
+        self.read(key).sismember(key, member)
+    }
+
+    pub fn range(&self, key: &str, start: i64, stop: i64) -> Result<Vec<String>, StoreError> {
+        self.read(key).range(key, start, stop)
+    }
+
+    pub fn lindex(&self, key: &str, index: i64) -> Result<Option<String>, StoreError> {
+        self.read(key).lindex(key, index)
+    }
+
+    pub fn lset(&self, key: &str, index: i64, value: &str) -> Result<(), StoreError> {
+        self.lock(key).lset(key, index, value)
+    }
+
+    pub fn pop(
+        &self,
+        key: &str,
+        count: usize,
+        end: ListEnd,
+    ) -> Result<Option<Vec<String>>, StoreError> {
+        self.lock(key).pop(key, count, end)
+    }
+}
+
+/// The data `Store::exclusive` moved into a private store, handed back to the
+/// shards it came from when dropped, so a panic while using it loses nothing.
+struct Lent<'a> {
+    guards: Vec<RwLockWriteGuard<'a, Keyspace>>,
+    store: Store,
+}
+
+impl Drop for Lent<'_> {
+    fn drop(&mut self) {
+        for (keyspace, shard) in self.guards.iter_mut().zip(self.store.shards.iter_mut()) {
+            **keyspace = std::mem::take(shard.get_mut().unwrap_or_else(PoisonError::into_inner));
+        }
+    }
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Periodically removes expired keys from every store so ones that are never read
+/// again don't linger. The thread exits once all of the stores have been dropped.
+pub fn spawn_expiry_sweeper(stores: &[Arc<Store>], interval: Duration) -> thread::JoinHandle<()> {
+    let stores: Vec<Weak<Store>> = stores.iter().map(Arc::downgrade).collect();
+
+    thread::spawn(move || {
+        loop {
+            thread::sleep(interval);
+
+            let mut alive = false;
+            for store in stores.iter().filter_map(Weak::upgrade) {
+                store.purge_expired();
+                alive = true;
+            }
+            if !alive {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn set_get_contains_and_remove() {
+        let store = Store::new();
+        assert_eq!(store.get("a"), Ok(None));
+        assert!(!store.contains("a"));
+
+        assert_eq!(store.set("a", "1"), Ok(None));
+        assert_eq!(store.get("a"), Ok(Some("1".to_string())));
+        assert!(store.contains("a"));
+        assert_eq!(store.set("a", "2"), Ok(Some(Value::Str("1".to_string()))));
+
+        assert_eq!(store.remove("a"), Some(Value::Str("2".to_string())));
+        assert_eq!(store.remove("a"), None);
+        assert!(!store.contains("a"));
+        assert_eq!(store.size(), 0);
+    }
+
+    #[test]
+    fn reading_a_string_from_another_type_is_wrongtype() {
+        let store = Store::new();
+        store
+            .push("list", &strings(&["x"]), ListEnd::Right)
+            .unwrap();
+        assert_eq!(store.get("list"), Err(StoreError::WrongType));
+        assert_eq!(store.incr_by("list", 1), Err(StoreError::WrongType));
+        assert_eq!(store.type_of("list"), "list");
+        assert_eq!(store.type_of("missing"), "none");
+    }
+
+    #[test]
+    fn keys_past_their_deadline_are_gone() {
+        let store = Store::new();
+        store.set("old", "1").unwrap();
+        store.set("new", "2").unwrap();
+        assert!(store.expire_at("old", SystemTime::now() - Duration::from_secs(1)));
+        assert!(store.expire_at("new", SystemTime::now() + Duration::from_secs(100)));
+
+        assert_eq!(store.get("old"), Ok(None));
+        assert_eq!(store.ttl("old"), Ttl::Missing);
+        assert!(matches!(store.ttl("new"), Ttl::Expires(_)));
+        assert!(store.persist("new"));
+        assert_eq!(store.ttl("new"), Ttl::Persistent);
+        assert!(!store.expire_at("missing", SystemTime::now()));
+    }
+
+    #[test]
+    fn counters_and_string_edits() {
+        let store = Store::new();
+        assert_eq!(store.incr_by("n", 5), Ok(5));
+        assert_eq!(store.incr_by("n", -7), Ok(-2));
+        store.set("text", "x").unwrap();
+        assert_eq!(store.incr_by("text", 1), Err(StoreError::NotAnInteger));
+        store.set("max", i64::MAX.to_string()).unwrap();
+        assert_eq!(store.incr_by("max", 1), Err(StoreError::Overflow));
+
+        assert_eq!(store.append("s", "hello"), Ok(5));
+        assert_eq!(store.setrange("s", 6, "world"), Ok(11));
+        assert_eq!(store.get("s"), Ok(Some("hello\0world".to_string())));
+        assert_eq!(store.getrange("s", -5, -1), Ok("world".to_string()));
+    }
+
+    #[test]
+    fn lists_hashes_and_sets() {
+        let store = Store::new();
+        assert_eq!(
+            store.push("l", &strings(&["b", "c"]), ListEnd::Right),
+            Ok(2)
+        );
+        assert_eq!(store.push("l", &strings(&["a"]), ListEnd::Left), Ok(3));
+        assert_eq!(store.range("l", 0, -1), Ok(strings(&["a", "b", "c"])));
+        assert_eq!(
+            store.pop("l", 3, ListEnd::Left),
+            Ok(Some(strings(&["a", "b", "c"])))
+        );
+        // An emptied container takes its key with it
+        assert!(!store.contains("l"));
+
+        let pairs = vec![("f".to_string(), "1".to_string())];
+        assert_eq!(store.hset("h", &pairs), Ok(1));
+        assert_eq!(store.hincr_by("h", "f", 2), Ok(3));
+        assert_eq!(store.hget("h", "f"), Ok(Some("3".to_string())));
+        assert_eq!(store.hdel("h", &strings(&["f"])), Ok(1));
+        assert!(!store.contains("h"));
+
+        assert_eq!(store.sadd("s", &strings(&["x", "y", "x"])), Ok(2));
+        assert_eq!(store.sismember("s", "y"), Ok(true));
+        assert_eq!(store.srem("s", &strings(&["x", "y"])), Ok(2));
+        assert!(!store.contains("s"));
+    }
+
+    #[test]
+    fn rename_and_copy_move_keys_between_shards() {
+        let store = Store::new();
+        store.set("a", "1").unwrap();
+        store.expire_at("a", SystemTime::now() + Duration::from_secs(100));
+
+        // Enough names that some land in another shard than `a`
+        for i in 0..32 {
+            let destination = format!("b{}", i);
+            assert!(store.rename("a", &destination));
+            assert!(!store.contains("a"));
+            assert!(matches!(store.ttl(&destination), Ttl::Expires(_)));
+            assert!(store.rename(&destination, "a"));
+        }
+        assert!(!store.rename("missing", "other"));
+
+        assert_eq!(store.copy("a", "c", false), Ok(true));
+        assert_eq!(store.copy("a", "c", false), Ok(false));
+        assert_eq!(store.get("c"), Ok(Some("1".to_string())));
+        assert_eq!(store.size(), 2);
+    }
+
+    #[test]
+    fn keys_scan_and_clear_see_every_shard() {
+        let store = Store::new();
+        for i in 0..100 {
+            store.set(format!("key{}", i), "v").unwrap();
+        }
+        store.set("other", "v").unwrap();
+
+        assert_eq!(store.keys("key*").len(), 100);
+        let mut matched = store.keys("key1?");
+        matched.sort();
+        let expected: Vec<String> = (10..20).map(|i| format!("key{}", i)).collect();
+        assert_eq!(matched, expected);
+
+        let mut seen = HashSet::new();
+        let mut cursor = 0;
+        loop {
+            let (next, keys) = store.scan(cursor, 10);
+            seen.extend(keys);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        assert_eq!(seen.len(), 101);
+
+        store.clear();
+        assert_eq!(store.size(), 0);
+        assert_eq!(store.random_key(), None);
+    }
+
+    #[test]
+    fn typed_accessors_return_a_value_of_their_type() {
+        let store = Store::new();
+        store.set("s", "text").unwrap();
+        store.push("l", &strings(&["a"]), ListEnd::Right).unwrap();
+        store
+            .hset("h", &[("f".to_string(), "1".to_string())])
+            .unwrap();
+        store.sadd("set", &strings(&["m"])).unwrap();
+
+        store.lock("s").get_str_mut("s").unwrap().push('!');
+        store
+            .lock("l")
+            .get_list_mut("l")
+            .unwrap()
+            .push_back("b".to_string());
+        store
+            .lock("h")
+            .get_hash_mut("h")
+            .unwrap()
+            .insert("g".to_string(), "2".to_string());
+        store
+            .lock("set")
+            .get_set_mut("set")
+            .unwrap()
+            .insert("n".to_string());
+
+        assert_eq!(store.get("s"), Ok(Some("text!".to_string())));
+        assert_eq!(store.range("l", 0, -1), Ok(strings(&["a", "b"])));
+        assert_eq!(store.hget("h", "g"), Ok(Some("2".to_string())));
+        assert_eq!(store.sismember("set", "n"), Ok(true));
+    }
+
+    #[test]
+    fn typed_accessors_refuse_other_types_without_changing_them() {
+        let store = Store::new();
+        store.set("s", "text").unwrap();
+        store.push("l", &strings(&["a"]), ListEnd::Right).unwrap();
+
+        let mut data = store.lock("s");
+        assert_eq!(data.get_list_mut("s").err(), Some(StoreError::WrongType));
+        assert_eq!(data.get_hash_mut("s").err(), Some(StoreError::WrongType));
+        assert_eq!(data.get_set_mut("s").err(), Some(StoreError::WrongType));
+        drop(data);
+        assert_eq!(
+            store.lock("l").get_str_mut("l").err(),
+            Some(StoreError::WrongType)
+        );
+
+        assert_eq!(store.get("s"), Ok(Some("text".to_string())));
+        assert_eq!(store.range("l", 0, -1), Ok(strings(&["a"])));
+    }
+
+    #[test]
+    fn typed_accessors_create_missing_keys_empty() {
+        let store = Store::new();
+        store
+            .lock("l")
+            .get_list_mut("l")
+            .unwrap()
+            .push_back("a".to_string());
+        store
+            .lock("h")
+            .get_hash_mut("h")
+            .unwrap()
+            .insert("f".to_string(), "1".to_string());
+        store
+            .lock("set")
+            .get_set_mut("set")
+            .unwrap()
+            .insert("m".to_string());
+        assert_eq!(
+            store.lock("s").get_str_mut("s").map(|value| value.clone()),
+            Ok(String::new())
+        );
+
+        assert_eq!(store.type_of("l"), "list");
+        assert_eq!(store.type_of("h"), "hash");
+        assert_eq!(store.type_of("set"), "set");
+        assert_eq!(store.type_of("s"), "string");
+
+        // An expired key is replaced rather than handed back
+        store.expire_at("l", SystemTime::now() - Duration::from_secs(1));
+        assert!(store.lock("l").get_list_mut("l").unwrap().is_empty());
+    }
+
+    #[test]
+    fn typed_accessors_report_wrongtype_before_a_full_store() {
+        let store = Store::with_shards(4, Some(1), EvictionPolicy::NoEviction);
+        store.set("s", "text").unwrap();
+
+        let mut data = store.lock("s");
+        assert_eq!(data.get_list_mut("s").err(), Some(StoreError::WrongType));
+        drop(data);
+        assert_eq!(
+            store.lock("new").get_list_mut("new").err(),
+            Some(StoreError::OutOfMemory)
+        );
+    }
+
+    #[test]
+    fn exclusive_keeps_the_data_when_its_caller_panics() {
+        let store = Store::new();
+        store.set("kept", "1").unwrap();
+
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            store.exclusive(|private| {
+                private.set("written", "2").unwrap();
+                panic!("handler failed");
+            })
+        }));
+
+        assert!(outcome.is_err());
+        assert_eq!(store.get("kept"), Ok(Some("1".to_string())));
+        assert_eq!(store.get("written"), Ok(Some("2".to_string())));
+        // The store is still usable afterwards
+        store.set("after", "3").unwrap();
+        assert_eq!(
+            store.exclusive(|private| private.get("after")),
+            Ok(Some("3".to_string()))
+        );
+    }
+}
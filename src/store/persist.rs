@@ -0,0 +1,363 @@
+//! Snapshot persistence for every database of a server.
+//!
+//! A snapshot starts with `MAGIC` and a format version, followed by one record per
+//! key and an end marker. A record holds the database index, the key, its deadline
+//! (milliseconds since the Unix epoch, or none) and the value. Strings are stored as
+//! a `u32` byte length followed by UTF-8 bytes, and collections as a `u32` count
+//! followed by their elements. All integers are little-endian.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::store::{Store, Value};
+
+const MAGIC: &[u8; 6] = b"RUSTDS";
+const VERSION: u8 = 1;
+
+const RECORD: u8 = 0x01;
+const END: u8 = 0xFF;
+
+const TAG_STR: u8 = 0;
+const TAG_LIST: u8 = 1;
+const TAG_HASH: u8 = 2;
+const TAG_SET: u8 = 3;
+
+// Distinguishes the temporary files of saves running at the same time
+static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Writes every live key of `databases` to `path`.
+///
+/// The snapshot is written to a temporary file that replaces `path` only once it is
+/// complete, so a crash mid-save leaves the previous snapshot intact. Each database
+/// is locked while it is written out.
+pub fn save(path: &Path, databases: &[Arc<Store>]) -> io::Result<()> {
+    let temp_id = NEXT_TEMP_ID.fetch_add(1, Ordering::SeqCst);
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".tmp-{}-{}", std::process::id(), temp_id));
+
+    let result = write_snapshot(Path::new(&temp), databases);
+    match result {
+        Ok(()) => fs::rename(&temp, path),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
+
+fn write_snapshot(path: &Path, databases: &[Arc<Store>]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&[VERSION])?;
+
+    for (index, store) in databases.iter().enumerate() {
+        // Every shard at once, so the snapshot is consistent across them
+        let shards = store.read_all();
+        for keyspace in shards.iter() {
+            for (key, value) in keyspace.data.iter() {
+                if keyspace.is_expired(key) {
+                    continue;
+                }
+
+                out.write_all(&[RECORD])?;
+                write_u32(&mut out, index)?;
+                write_str(&mut out, key)?;
+                match keyspace.expires.get(key) {
+                    Some(deadline) => {
+                        let millis = deadline
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_millis();
+                        out.write_all(&[1])?;
+                        out.write_all(&u64::try_from(millis).unwrap_or(u64::MAX).to_le_bytes())?;
+                    }
+                    None => out.write_all(&[0])?,
+                }
+                write_value(&mut out, value)?;
+            }
+        }
+    }
+
+    out.write_all(&[END])?;
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()
+}
+
+fn write_value(out: &mut impl Write, value: &Value) -> io::Result<()> {
+    match value {
+        Value::Str(text) => {
+            out.write_all(&[TAG_STR])?;
+            write_str(out, text)
+        }
+        Value::List(items) => {
+            out.write_all(&[TAG_LIST])?;
+            write_u32(out, items.len())?;
+            items.iter().try_for_each(|item| write_str(out, item))
+        }
+        Value::Hash(fields) => {
+            out.write_all(&[TAG_HASH])?;
+            write_u32(out, fields.len())?;
+            fields.iter().try_for_each(|(field, value)| {
+                write_str(out, field)?;
+                write_str(out, value)
+            })
+        }
+        Value::Set(members) => {
+            out.write_all(&[TAG_SET])?;
+            write_u32(out, members.len())?;
+            members.iter().try_for_each(|member| write_str(out, member))
+        }
+    }
+}
+
+fn write_u32(out: &mut impl Write, value: usize) -> io::Result<()> {
+    let value = u32::try_from(value)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "value too large to save"))?;
+    out.write_all(&value.to_le_bytes())
+}
+
+fn write_str(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write_u32(out, text.len())?;
+    out.write_all(text.as_bytes())
+}
+
+/// Loads the snapshot at `path` into `databases`, replacing keys with the same name.
+///
+/// Records are read one at a time and stored as they are read, so loading needs
+/// little memory beyond the data itself. A corrupt or truncated snapshot fails with
+/// an `InvalidData` error naming the record at fault, and keys from the records
+/// before it stay loaded. Keys whose deadline passed while the server was down are
+/// skipped.
+pub fn load(path: &Path, databases: &[Arc<Store>]) -> io::Result<()> {
+    let mut input = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 6];
+    read_exact(&mut input, &mut magic)?;
+    if &magic != MAGIC {
+        return Err(corrupt("not a snapshot file"));
+    }
+    let version = read_u8(&mut input)?;
+    if version != VERSION {
+        return Err(corrupt(format!("unsupported snapshot version {}", version)));
+    }
+
+    let now = SystemTime::now();
+    for entry in 0.. {
+        let record = read_record(&mut input, databases.len())
+            .map_err(|e| io::Error::new(e.kind(), format!("{} (at entry {})", e, entry)))?;
+        let Some(Record {
+            db,
+            key,
+            deadline,
+            value,
+        }) = record
+        else {
+            break;
+        };
+        if deadline.is_some_and(|deadline| deadline <= now) {
+            continue;
+        }
+
+        // Under noeviction a snapshot bigger than the key limit can't be loaded whole
+        databases[db]
+            .write(&key, |keyspace| {
+                keyspace.set(key.as_str(), value)?;
+                if let Some(deadline) = deadline {
+                    keyspace.expire_at(&key, deadline);
+                }
+                Ok(())
+            })
+            .map_err(|e| io::Error::other(format!("{} (at entry {})", e, entry)))?;
+    }
+
+    Ok(())
+}
+
+// One key as saved in a snapshot
+struct Record {
+    db: usize,
+    key: String,
+    deadline: Option<SystemTime>,
+    value: Value,
+}
+
+/// The next record, or None at the end marker. `databases` is how many databases
+/// there are to load into.
+fn read_record(input: &mut impl Read, databases: usize) -> io::Result<Option<Record>> {
+    match read_u8(input)? {
+        RECORD => {}
+        END => return Ok(None),
+        other => return Err(corrupt(format!("unexpected record type {}", other))),
+    }
+
+    let db = read_u32(input)?;
+    if db >= databases {
+        return Err(corrupt(format!(
+            "snapshot has database {} but only {} are configured",
+            db, databases
+        )));
+    }
+    let key = read_string(input)?;
+    let deadline = match read_u8(input)? {
+        0 => None,
+        1 => {
+            let mut millis = [0u8; 8];
+            read_exact(input, &mut millis)?;
+            Some(UNIX_EPOCH + Duration::from_millis(u64::from_le_bytes(millis)))
+        }
+        other => return Err(corrupt(format!("unexpected expiry flag {}", other))),
+    };
+    let value = read_value(input)?;
+
+    Ok(Some(Record {
+        db,
+        key,
+        deadline,
+        value,
+    }))
+}
+
+fn read_value(input: &mut impl Read) -> io::Result<Value> {
+    let value = match read_u8(input)? {
+        TAG_STR => Value::Str(read_string(input)?),
+        TAG_LIST => {
+            let count = read_u32(input)?;
+            let mut items = VecDeque::new();
+            for _ in 0..count {
+                items.push_back(read_string(input)?);
+            }
+            Value::List(items)
+        }
+        TAG_HASH => {
+            let count = read_u32(input)?;
+            let mut fields = HashMap::new();
+            for _ in 0..count {
+                fields.insert(read_string(input)?, read_string(input)?);
+            }
+            Value::Hash(fields)
+        }
+        TAG_SET => {
+            let count = read_u32(input)?;
+            let mut members = HashSet::new();
+            for _ in 0..count {
+                members.insert(read_string(input)?);
+            }
+            Value::Set(members)
+        }
+        other => return Err(corrupt(format!("unexpected value type {}", other))),
+    };
+
+    if value.is_empty_container() {
+        return Err(corrupt("empty collection"));
+    }
+    Ok(value)
+}
+
+// A file that ends early is corrupt rather than an ordinary I/O failure
+fn read_exact(input: &mut impl Read, buf: &mut [u8]) -> io::Result<()> {
+    input.read_exact(buf).map_err(|e| {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            corrupt("snapshot ends unexpectedly")
+        } else {
+            e
+        }
+    })
+}
+
+fn read_u8(input: &mut impl Read) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    read_exact(input, &mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<usize> {
+    let mut bytes = [0u8; 4];
+    read_exact(input, &mut bytes)?;
+    Ok(u32::from_le_bytes(bytes) as usize)
+}
+
+fn read_string(input: &mut impl Read) -> io::Result<String> {
+    let len = read_u32(input)?;
+    let mut bytes = Vec::new();
+    // take() keeps a bogus length from allocating more than the file holds
+    input.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(corrupt("snapshot ends unexpectedly"));
+    }
+    String::from_utf8(bytes).map_err(|_| corrupt("string is not valid UTF-8"))
+}
+
+fn corrupt(message: impl Into<String>) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("corrupt snapshot: {}", message.into()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A file in the temp directory no other test uses
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rustdes-persist-{}-{}", std::process::id(), name))
+    }
+
+    fn databases(count: usize) -> Vec<Arc<Store>> {
+        (0..count).map(|_| Arc::new(Store::new())).collect()
+    }
+
+    #[test]
+    fn a_multi_megabyte_snapshot_loads_whole() {
+        let path = temp_path("large.rdb");
+        let saved = databases(2);
+        let filler = "x".repeat(256);
+        for i in 0..20_000 {
+            saved[i % 2]
+                .set(format!("key{}", i), format!("{}{}", filler, i))
+                .unwrap();
+        }
+        let items = ["a".to_string(), "b".to_string()];
+        saved[1]
+            .push("list", &items, crate::store::ListEnd::Right)
+            .unwrap();
+        save(&path, &saved).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() > 5_000_000);
+
+        let loaded = databases(2);
+        load(&path, &loaded).unwrap();
+        assert_eq!(loaded[0].size(), 10_000);
+        assert_eq!(loaded[1].size(), 10_001);
+        assert_eq!(
+            loaded[1].get("key19999"),
+            Ok(Some(format!("{}19999", filler)))
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn corruption_names_the_entry_that_failed() {
+        let path = temp_path("truncated.rdb");
+        let saved = databases(1);
+        for i in 0..3 {
+            saved[0].set(format!("key{}", i), "value").unwrap();
+        }
+        save(&path, &saved).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        // Cut into the last record's value
+        fs::write(&path, &bytes[..bytes.len() - 3]).unwrap();
+
+        let loaded = databases(1);
+        let err = load(&path, &loaded).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("at entry 2"), "{}", err);
+        assert_eq!(loaded[0].size(), 2);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A value held in the store. Each key maps to exactly one kind of value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Str(String),
+    List(VecDeque<String>),
+    Hash(HashMap<String, String>),
+    Set(HashSet<String>),
+}
+
+// Limits below which Redis keeps values in its compact encodings, by default
+const EMBSTR_MAX_LEN: usize = 44;
+const LISTPACK_MAX_ENTRIES: usize = 128;
+const LISTPACK_MAX_VALUE_LEN: usize = 64;
+const INTSET_MAX_ENTRIES: usize = 512;
+
+/// Which end of a list an operation applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
+
+/// How SINTER, SUNION and SDIFF combine their sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperation {
+    Intersection,
+    Union,
+    Difference,
+}
+
+impl Value {
+    /// Name of the value's type as reported by TYPE.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+            Value::Hash(_) => "hash",
+            Value::Set(_) => "set",
+        }
+    }
+
+    /// Name of the representation Redis would use for the value, as reported by
+    /// OBJECT ENCODING. Everything is stored the same way here, so this only
+    /// mirrors Redis's rules for when it switches from a compact form to a general one.
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Value::Str(value) if value.parse::<i64>().is_ok() => "int",
+            Value::Str(value) if value.len() <= EMBSTR_MAX_LEN => "embstr",
+            Value::Str(_) => "raw",
+            Value::List(list) if fits_listpack(list.len(), list.iter()) => "listpack",
+            Value::List(_) => "quicklist",
+            Value::Hash(hash)
+                if fits_listpack(hash.len(), hash.iter().flat_map(|(k, v)| [k, v])) =>
+            {
+                "listpack"
+            }
+            Value::Hash(_) => "hashtable",
+            Value::Set(set)
+                if set.len() <= INTSET_MAX_ENTRIES
+                    && set.iter().all(|member| member.parse::<i64>().is_ok()) =>
+            {
+                "intset"
+            }
+            Value::Set(set) if fits_listpack(set.len(), set.iter()) => "listpack",
+            Value::Set(_) => "hashtable",
+        }
+    }
+
+    /// Whether the value is an empty container. Empty containers are never kept in
+    /// the store: removing the last element of one removes the key.
+    pub fn is_empty_container(&self) -> bool {
+        match self {
+            Value::Str(_) => false,
+            Value::List(list) => list.is_empty(),
+            Value::Hash(hash) => hash.is_empty(),
+            Value::Set(set) => set.is_empty(),
+        }
+    }
+}
+
+// Whether a collection of `len` entries made of `items` is small enough for a listpack
+fn fits_listpack<'a>(len: usize, mut items: impl Iterator<Item = &'a String>) -> bool {
+    len <= LISTPACK_MAX_ENTRIES && items.all(|item| item.len() <= LISTPACK_MAX_VALUE_LEN)
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Str(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Str(value.to_string())
+    }
+}
@@ -0,0 +1,43 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time elapsed between `earlier` and `later`, or zero if the clock moved backwards.
+pub fn duration_between(earlier: SystemTime, later: SystemTime) -> Duration {
+    later.duration_since(earlier).unwrap_or(Duration::ZERO)
+}
+
+/// Time elapsed since `earlier`, saturating to zero on clock skew.
+pub fn elapsed_since(earlier: SystemTime) -> Duration {
+    duration_between(earlier, SystemTime::now())
+}
+
+/// Milliseconds from the Unix epoch to `time`, or zero for a time before it.
+pub fn unix_millis(time: SystemTime) -> u64 {
+    let millis = duration_between(UNIX_EPOCH, time).as_millis();
+    u64::try_from(millis).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_saturate_when_the_clock_goes_backwards() {
+        let earlier = UNIX_EPOCH + Duration::from_secs(100);
+        let later = earlier + Duration::from_millis(1500);
+        assert_eq!(
+            duration_between(earlier, later),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(duration_between(later, earlier), Duration::ZERO);
+        assert_eq!(
+            elapsed_since(SystemTime::now() + Duration::from_secs(60)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn unix_millis_clamps_to_the_epoch() {
+        assert_eq!(unix_millis(UNIX_EPOCH + Duration::from_millis(1234)), 1234);
+        assert_eq!(unix_millis(UNIX_EPOCH - Duration::from_secs(1)), 0);
+    }
+}
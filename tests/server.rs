@@ -0,0 +1,2166 @@
+//! End-to-end tests that start a real server on a free port and talk RESP to it.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustdes::config::Config;
+use rustdes::server::resp::RespValue;
+use rustdes::server::server::{ServerHandle, spawn_server};
+use rustdes::store::EvictionPolicy;
+
+// Long enough for any reply; a deadlocked server fails the test instead of hanging it
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn config() -> Config {
+    Config {
+        port: 0,
+        snapshot_path: None,
+        ..Config::default()
+    }
+}
+
+fn start(config: &Config) -> (ServerHandle, SocketAddr) {
+    let server = spawn_server(&[0], config).expect("server should start");
+    let addr = server.local_addrs()[0];
+    (server, addr)
+}
+
+struct Client {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl Client {
+    fn connect(addr: SocketAddr) -> Self {
+        let stream = TcpStream::connect(addr).expect("server should accept");
+        stream.set_read_timeout(Some(REPLY_TIMEOUT)).unwrap();
+        Self {
+            stream,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn send_raw(&mut self, bytes: &[u8]) {
+        self.stream.write_all(bytes).unwrap();
+    }
+
+    /// The next reply, or None if the server closed the connection first.
+    fn reply(&mut self) -> Option<RespValue> {
+        loop {
+            if let Some((value, used)) = RespValue::decode(&self.buffer).unwrap() {
+                self.buffer.drain(..used);
+                return Some(value);
+            }
+            let mut chunk = [0; 4096];
+            let read = self.stream.read(&mut chunk).expect("reply should arrive");
+            if read == 0 {
+                return None;
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    /// The next line of an inline reply, without its newline.
+    fn line(&mut self) -> String {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line = String::from_utf8(self.buffer[..end].to_vec()).unwrap();
+                self.buffer.drain(..=end);
+                return line;
+            }
+            let mut chunk = [0; 4096];
+            let read = self.stream.read(&mut chunk).expect("reply should arrive");
+            assert!(read > 0, "connection closed before the reply");
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    fn send(&mut self, args: &[&str]) {
+        let command = RespValue::Array(Some(
+            args.iter()
+                .map(|arg| RespValue::BulkString(Some(arg.as_bytes().to_vec())))
+                .collect(),
+        ));
+        self.send_raw(&command.encode());
+    }
+
+    fn call(&mut self, args: &[&str]) -> RespValue {
+        self.send(args);
+        self.reply().expect("connection closed before the reply")
+    }
+}
+
+fn ok() -> RespValue {
+    RespValue::SimpleString("OK".to_string())
+}
+
+fn pong() -> RespValue {
+    RespValue::SimpleString("PONG".to_string())
+}
+
+fn bulk(value: &str) -> RespValue {
+    RespValue::BulkString(Some(value.as_bytes().to_vec()))
+}
+
+fn is_error(reply: &RespValue, prefix: &str) -> bool {
+    matches!(reply, RespValue::Error(message) if message.starts_with(prefix))
+}
+
+// A file in the temp directory no other test run uses
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rustdes-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn commands_that_reach_other_databases_are_refused_inside_multi() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+    let mut other = Client::connect(addr);
+
+    assert_eq!(client.call(&["SET", "a", "1"]), ok());
+    for command in [
+        &["INFO", "keyspace"][..],
+        &["SAVE"],
+        &["FLUSHALL"],
+        &["SELECT", "1"],
+    ] {
+        assert_eq!(client.call(&["MULTI"]), ok());
+        let refused = client.call(command);
+        assert!(
+            is_error(&refused, "ERR"),
+            "{:?} gave {:?}",
+            command,
+            refused
+        );
+        assert!(is_error(&client.call(&["EXEC"]), "EXECABORT"));
+    }
+
+    // Nothing was left locked by the aborted transactions
+    assert_eq!(other.call(&["GET", "a"]), bulk("1"));
+
+    assert_eq!(client.call(&["MULTI"]), ok());
+    client.call(&["SET", "b", "2"]);
+    client.call(&["GET", "a"]);
+    assert_eq!(
+        client.call(&["EXEC"]),
+        RespValue::Array(Some(vec![ok(), bulk("1")]))
+    );
+    assert_eq!(other.call(&["GET", "b"]), bulk("2"));
+
+    server.shutdown();
+}
+
+#[test]
+fn exec_runs_the_queued_commands_together() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+    let mut other = Client::connect(addr);
+
+    assert_eq!(client.call(&["MULTI"]), ok());
+    assert_eq!(
+        client.call(&["SET", "a", "1"]),
+        RespValue::SimpleString("QUEUED".to_string())
+    );
+    client.call(&["INCR", "a"]);
+    client.call(&["GET", "a"]);
+    // Nothing has run yet
+    assert_eq!(other.call(&["GET", "a"]), RespValue::BulkString(None));
+    assert_eq!(
+        client.call(&["EXEC"]),
+        RespValue::Array(Some(vec![ok(), RespValue::Integer(2), bulk("2")]))
+    );
+    assert_eq!(other.call(&["GET", "a"]), bulk("2"));
+
+    server.shutdown();
+}
+
+#[test]
+fn discard_drops_the_queued_commands() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["MULTI"]), ok());
+    client.call(&["SET", "a", "1"]);
+    assert_eq!(client.call(&["DISCARD"]), ok());
+    assert_eq!(client.call(&["GET", "a"]), RespValue::BulkString(None));
+    assert!(is_error(&client.call(&["EXEC"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn exec_and_discard_without_multi_are_errors() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert!(is_error(&client.call(&["EXEC"]), "ERR"));
+    assert!(is_error(&client.call(&["DISCARD"]), "ERR"));
+    // The connection carries on as normal
+    assert_eq!(client.call(&["SET", "a", "1"]), ok());
+
+    server.shutdown();
+}
+
+#[test]
+fn a_queued_command_with_bad_arguments_aborts_exec() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["MULTI"]), ok());
+    client.call(&["SET", "a", "1"]);
+    assert!(is_error(&client.call(&["GET"]), "ERR"));
+    assert!(is_error(&client.call(&["EXEC"]), "EXECABORT"));
+    assert_eq!(client.call(&["GET", "a"]), RespValue::BulkString(None));
+
+    server.shutdown();
+}
+
+#[test]
+fn deeply_nested_arrays_are_rejected_without_crashing() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.send_raw(&b"*1\r\n".repeat(200_000));
+    if let Some(reply) = client.reply() {
+        assert!(is_error(&reply, "ERR"), "got {:?}", reply);
+    }
+
+    let mut other = Client::connect(addr);
+    assert_eq!(other.call(&["PING"]), pong());
+
+    server.shutdown();
+}
+
+#[test]
+fn flushall_clears_every_database_and_flushdb_only_the_selected_one() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "a", "1"]);
+    client.call(&["SELECT", "1"]);
+    client.call(&["SET", "b", "2"]);
+    assert_eq!(client.call(&["FLUSHDB"]), ok());
+    assert_eq!(client.call(&["DBSIZE"]), RespValue::Integer(0));
+    client.call(&["SELECT", "0"]);
+    assert_eq!(client.call(&["DBSIZE"]), RespValue::Integer(1));
+
+    client.call(&["SELECT", "2"]);
+    client.call(&["SET", "c", "3"]);
+    assert_eq!(client.call(&["FLUSHALL"]), ok());
+    for db in ["0", "1", "2"] {
+        client.call(&["SELECT", db]);
+        assert_eq!(client.call(&["DBSIZE"]), RespValue::Integer(0), "db {}", db);
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn aof_replay_keeps_expiry_deadlines() {
+    let path = temp_path("expiry.aof");
+    let config = Config {
+        appendonly: Some(path.clone()),
+        ..config()
+    };
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    client.call(&["SET", "short", "1"]);
+    client.call(&["PEXPIRE", "short", "200"]);
+    client.call(&["SETEX", "long", "100", "2"]);
+    client.call(&["SET", "kept", "3"]);
+    client.call(&["EXPIRE", "kept", "100"]);
+    server.shutdown();
+
+    // Long enough for a relative timeout to show up as time gained on replay
+    thread::sleep(Duration::from_millis(1100));
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    assert_eq!(client.call(&["GET", "short"]), RespValue::BulkString(None));
+    assert_eq!(client.call(&["GET", "long"]), bulk("2"));
+    for key in ["long", "kept"] {
+        let RespValue::Integer(ttl) = client.call(&["TTL", key]) else {
+            panic!("TTL should be an integer");
+        };
+        assert!((1..100).contains(&ttl), "{} has {}s left", key, ttl);
+    }
+    server.shutdown();
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn aof_replay_rebuilds_the_data_after_a_restart() {
+    let path = temp_path("restart.aof");
+    let config = Config {
+        appendonly: Some(path.clone()),
+        ..config()
+    };
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    client.call(&["SET", "name", "rustdes"]);
+    client.call(&["APPEND", "name", "!"]);
+    client.call(&["INCRBY", "hits", "5"]);
+    client.call(&["RPUSH", "queue", "a", "b", "c"]);
+    client.call(&["LPOP", "queue"]);
+    client.call(&["HSET", "user", "id", "7"]);
+    client.call(&["SELECT", "3"]);
+    client.call(&["SADD", "tags", "x", "y"]);
+    client.call(&["GET", "name"]);
+    server.shutdown();
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    assert_eq!(client.call(&["GET", "name"]), bulk("rustdes!"));
+    assert_eq!(client.call(&["GET", "hits"]), bulk("5"));
+    assert_eq!(
+        client.call(&["LRANGE", "queue", "0", "-1"]),
+        RespValue::Array(Some(vec![bulk("b"), bulk("c")]))
+    );
+    assert_eq!(client.call(&["HGET", "user", "id"]), bulk("7"));
+    assert_eq!(client.call(&["DBSIZE"]), RespValue::Integer(4));
+    client.call(&["SELECT", "3"]);
+    assert_eq!(client.call(&["SCARD", "tags"]), RespValue::Integer(2));
+    server.shutdown();
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn aof_replay_skips_commands_that_no_longer_apply() {
+    let path = temp_path("stale.aof");
+    let config = Config {
+        appendonly: Some(path.clone()),
+        ..config()
+    };
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    client.call(&["RPUSH", "k", "a"]);
+    client.call(&["PEXPIRE", "k", "300"]);
+    assert_eq!(client.call(&["LSET", "k", "0", "b"]), ok());
+    client.call(&["SET", "after", "1"]);
+    server.shutdown();
+
+    // The list is gone by the time LSET is replayed, so it fails on the second run
+    thread::sleep(Duration::from_millis(400));
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    assert_eq!(client.call(&["EXISTS", "k"]), RespValue::Integer(0));
+    assert_eq!(client.call(&["GET", "after"]), bulk("1"));
+    server.shutdown();
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn connections_beyond_the_pool_wait_for_a_worker() {
+    let config = Config {
+        workers: 2,
+        ..config()
+    };
+    let (server, addr) = start(&config);
+
+    let mut clients: Vec<Client> = (0..6).map(|_| Client::connect(addr)).collect();
+    for client in &mut clients {
+        client.send(&["PING"]);
+    }
+    // Each one is answered once the connections ahead of it close
+    for mut client in clients {
+        assert_eq!(client.reply(), Some(pong()));
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn lru_eviction_makes_room_from_any_shard() {
+    let config = Config {
+        maxkeys: Some(3),
+        eviction_policy: EvictionPolicy::AllKeysLru,
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+
+    // Far more keys than the limit, landing in shards that may already be empty
+    for i in 0..50 {
+        let key = format!("key{}", i);
+        assert_eq!(client.call(&["SET", &key, "1"]), ok(), "{}", key);
+    }
+    assert_eq!(client.call(&["MSET", "a", "1", "b", "2"]), ok());
+    assert_eq!(client.call(&["DBSIZE"]), RespValue::Integer(3));
+    assert_eq!(client.call(&["GET", "key49"]), bulk("1"));
+
+    server.shutdown();
+}
+
+#[test]
+fn concurrent_writers_across_shards_do_not_deadlock() {
+    let config = Config {
+        maxkeys: Some(20),
+        eviction_policy: EvictionPolicy::AllKeysLru,
+        ..config()
+    };
+    let (server, addr) = start(&config);
+
+    // Multi-key writes lock several shards and evictions lock them all
+    let writers: Vec<_> = (0..8)
+        .map(|writer| {
+            thread::spawn(move || {
+                let mut client = Client::connect(addr);
+                for i in 0..200 {
+                    let first = format!("w{}-{}", writer, i);
+                    let second = format!("w{}-{}", (writer + 1) % 8, i + 1000);
+                    let reply = client.call(&["MSET", &first, "1", &second, "2"]);
+                    assert_eq!(reply, ok());
+                    client.call(&["COPY", &first, &format!("copy{}", i % 30)]);
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().expect("writer should finish");
+    }
+
+    let mut client = Client::connect(addr);
+    assert_eq!(client.call(&["DBSIZE"]), RespValue::Integer(20));
+
+    server.shutdown();
+}
+
+fn limited(policy: EvictionPolicy) -> Config {
+    Config {
+        maxkeys: Some(3),
+        eviction_policy: policy,
+        ..config()
+    }
+}
+
+fn exists(client: &mut Client, key: &str) -> bool {
+    client.call(&["EXISTS", key]) == RespValue::Integer(1)
+}
+
+#[test]
+fn noeviction_refuses_new_keys_at_the_limit() {
+    let (server, addr) = start(&limited(EvictionPolicy::NoEviction));
+    let mut client = Client::connect(addr);
+
+    for key in ["a", "b", "c"] {
+        assert_eq!(client.call(&["SET", key, "1"]), ok());
+    }
+    assert!(is_error(&client.call(&["SET", "d", "1"]), "OOM"));
+    assert!(is_error(&client.call(&["MSET", "a", "2", "d", "2"]), "OOM"));
+    // Existing keys can still change, and removing one makes room again
+    assert_eq!(client.call(&["SET", "a", "3"]), ok());
+    assert_eq!(client.call(&["DEL", "b"]), RespValue::Integer(1));
+    assert_eq!(client.call(&["SET", "d", "1"]), ok());
+
+    for (key, kept) in [("a", true), ("b", false), ("c", true), ("d", true)] {
+        assert_eq!(exists(&mut client, key), kept, "{}", key);
+    }
+    assert_eq!(client.call(&["GET", "a"]), bulk("3"));
+
+    server.shutdown();
+}
+
+#[test]
+fn allkeys_lru_evicts_the_least_recently_used_keys() {
+    let (server, addr) = start(&limited(EvictionPolicy::AllKeysLru));
+    let mut client = Client::connect(addr);
+
+    for key in ["a", "b", "c"] {
+        client.call(&["SET", key, "1"]);
+    }
+    client.call(&["GET", "a"]);
+    assert_eq!(client.call(&["SET", "d", "1"]), ok());
+    client.call(&["GET", "a"]);
+    client.call(&["GET", "c"]);
+    assert_eq!(client.call(&["SET", "e", "1"]), ok());
+
+    for (key, kept) in [
+        ("a", true),
+        ("b", false),
+        ("c", true),
+        ("d", false),
+        ("e", true),
+    ] {
+        assert_eq!(exists(&mut client, key), kept, "{}", key);
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn allkeys_random_keeps_the_new_key_and_stays_at_the_limit() {
+    let (server, addr) = start(&limited(EvictionPolicy::AllKeysRandom));
+    let mut client = Client::connect(addr);
+
+    for key in ["a", "b", "c"] {
+        client.call(&["SET", key, "1"]);
+    }
+    assert_eq!(client.call(&["SET", "d", "1"]), ok());
+
+    assert!(exists(&mut client, "d"));
+    let older = ["a", "b", "c"]
+        .into_iter()
+        .filter(|key| exists(&mut client, key))
+        .count();
+    assert_eq!(older, 2);
+    assert_eq!(client.call(&["DBSIZE"]), RespValue::Integer(3));
+
+    server.shutdown();
+}
+
+#[test]
+fn evicted_keys_stay_gone_after_an_aof_replay() {
+    let path = temp_path("evicted.aof");
+    let config = Config {
+        appendonly: Some(path.clone()),
+        ..limited(EvictionPolicy::AllKeysLru)
+    };
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    for key in ["a", "b", "c", "d", "e"] {
+        client.call(&["SET", key, "1"]);
+    }
+    server.shutdown();
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    for (key, kept) in [
+        ("a", false),
+        ("b", false),
+        ("c", true),
+        ("d", true),
+        ("e", true),
+    ] {
+        assert_eq!(exists(&mut client, key), kept, "{}", key);
+    }
+    server.shutdown();
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn a_panicking_command_leaves_other_connections_and_the_data_intact() {
+    let config = Config {
+        enable_debug_command: true,
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    let mut other = Client::connect(addr);
+
+    client.call(&["SET", "a", "1"]);
+    assert!(is_error(&client.call(&["DEBUG", "PANIC"]), "ERR"));
+
+    // Inside EXEC the panic happens while every shard of the database is locked
+    assert_eq!(client.call(&["MULTI"]), ok());
+    client.call(&["SET", "b", "2"]);
+    client.call(&["DEBUG", "PANIC"]);
+    client.call(&["SET", "c", "3"]);
+    let RespValue::Array(Some(replies)) = client.call(&["EXEC"]) else {
+        panic!("EXEC should reply with an array");
+    };
+    assert_eq!(replies[0], ok());
+    assert!(is_error(&replies[1], "ERR"));
+    assert_eq!(replies[2], ok());
+
+    assert_eq!(other.call(&["GET", "a"]), bulk("1"));
+    assert_eq!(other.call(&["GET", "b"]), bulk("2"));
+    assert_eq!(other.call(&["GET", "c"]), bulk("3"));
+    assert_eq!(other.call(&["SET", "d", "4"]), ok());
+    assert_eq!(
+        Client::connect(addr).call(&["DBSIZE"]),
+        RespValue::Integer(4)
+    );
+    assert_eq!(client.call(&["GET", "d"]), bulk("4"));
+
+    server.shutdown();
+}
+
+#[test]
+fn shutdown_finishes_running_commands_then_stops_listening() {
+    let config = Config {
+        enable_debug_command: true,
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut idle = Client::connect(addr);
+    let mut busy = Client::connect(addr);
+    assert_eq!(idle.call(&["SET", "a", "1"]), ok());
+
+    busy.send(&["DEBUG", "SLEEP", "0.3"]);
+    thread::sleep(Duration::from_millis(100));
+    server.shutdown();
+
+    // The command under way still got its reply before the connection closed
+    assert_eq!(busy.reply(), Some(ok()));
+    assert_eq!(busy.reply(), None);
+    assert_eq!(idle.reply(), None);
+    assert!(TcpStream::connect(addr).is_err());
+}
+
+#[test]
+fn ping_replies_pong_as_a_status_and_echoes_a_message() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["PING"]), pong());
+    assert_eq!(client.call(&["PING", "hello"]), bulk("hello"));
+    assert_eq!(client.call(&["MULTI"]), ok());
+    client.call(&["PING"]);
+    assert_eq!(client.call(&["EXEC"]), RespValue::Array(Some(vec![pong()])));
+
+    server.shutdown();
+}
+
+#[test]
+fn other_connections_are_served_while_one_sleeps() {
+    let config = Config {
+        enable_debug_command: true,
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut sleeper = Client::connect(addr);
+    let mut other = Client::connect(addr);
+
+    let started = Instant::now();
+    sleeper.send(&["DEBUG", "SLEEP", "1"]);
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(other.call(&["SET", "a", "1"]), ok());
+    assert_eq!(other.call(&["GET", "a"]), bulk("1"));
+    assert!(started.elapsed() < Duration::from_millis(900));
+
+    assert_eq!(sleeper.reply(), Some(ok()));
+    assert!(started.elapsed() >= Duration::from_secs(1));
+
+    server.shutdown();
+}
+
+#[test]
+fn every_listening_port_serves_the_same_data() {
+    let server = spawn_server(&[0, 0], &config()).expect("server should start");
+    let addrs = server.local_addrs().to_vec();
+    assert_eq!(addrs.len(), 2);
+    assert_ne!(addrs[0].port(), addrs[1].port());
+
+    let mut first = Client::connect(addrs[0]);
+    let mut second = Client::connect(addrs[1]);
+    assert_eq!(first.call(&["SET", "shared", "1"]), ok());
+    assert_eq!(second.call(&["GET", "shared"]), bulk("1"));
+
+    server.shutdown();
+    for addr in addrs {
+        assert!(
+            TcpStream::connect(addr).is_err(),
+            "{} still listening",
+            addr
+        );
+    }
+}
+
+fn int(value: i64) -> RespValue {
+    RespValue::Integer(value)
+}
+
+fn nil() -> RespValue {
+    RespValue::BulkString(None)
+}
+
+fn array(items: &[&str]) -> RespValue {
+    RespValue::Array(Some(items.iter().map(|item| bulk(item)).collect()))
+}
+
+#[test]
+fn get_reads_back_what_set_stored() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["GET", "missing"]), nil());
+    assert_eq!(client.call(&["SET", "greeting", "hello world"]), ok());
+    assert_eq!(client.call(&["GET", "greeting"]), bulk("hello world"));
+    assert!(is_error(&client.call(&["GET"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn set_creates_overwrites_and_accepts_empty_values() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["SET", "k", "first"]), ok());
+    assert_eq!(client.call(&["SET", "k", "second"]), ok());
+    assert_eq!(client.call(&["GET", "k"]), bulk("second"));
+    assert_eq!(client.call(&["SET", "empty", ""]), ok());
+    assert_eq!(client.call(&["GET", "empty"]), bulk(""));
+    assert_eq!(client.call(&["EXISTS", "empty"]), int(1));
+    assert!(is_error(&client.call(&["SET", "k"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn exists_counts_present_keys_including_repeats() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "a", "1"]);
+    client.call(&["SET", "b", "2"]);
+    assert_eq!(client.call(&["EXISTS", "a"]), int(1));
+    assert_eq!(client.call(&["EXISTS", "missing"]), int(0));
+    assert_eq!(client.call(&["EXISTS", "a", "b", "missing"]), int(2));
+    assert_eq!(client.call(&["EXISTS", "a", "a", "a"]), int(3));
+
+    server.shutdown();
+}
+
+#[test]
+fn expire_removes_a_key_once_its_time_is_up() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "short", "1"]);
+    client.call(&["SET", "long", "2"]);
+    assert_eq!(client.call(&["EXPIRE", "short", "1"]), int(1));
+    assert_eq!(client.call(&["EXPIRE", "long", "100"]), int(1));
+    assert_eq!(client.call(&["EXPIRE", "missing", "1"]), int(0));
+    assert_eq!(client.call(&["GET", "short"]), bulk("1"));
+
+    thread::sleep(Duration::from_millis(1100));
+    assert_eq!(client.call(&["GET", "short"]), nil());
+    assert_eq!(client.call(&["EXISTS", "short"]), int(0));
+    assert_eq!(client.call(&["GET", "long"]), bulk("2"));
+    assert_eq!(client.call(&["DBSIZE"]), int(1));
+
+    server.shutdown();
+}
+
+#[test]
+fn ttl_tells_missing_persistent_and_expiring_keys_apart() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "forever", "1"]);
+    client.call(&["SET", "expiring", "1"]);
+    client.call(&["EXPIRE", "expiring", "100"]);
+    assert_eq!(client.call(&["TTL", "missing"]), int(-2));
+    assert_eq!(client.call(&["TTL", "forever"]), int(-1));
+    assert_eq!(client.call(&["TTL", "expiring"]), int(100));
+
+    server.shutdown();
+}
+
+#[test]
+fn incr_and_decr_count_from_zero_and_refuse_bad_values() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["INCR", "n"]), int(1));
+    assert_eq!(client.call(&["INCR", "n"]), int(2));
+    assert_eq!(client.call(&["DECR", "n"]), int(1));
+    assert_eq!(client.call(&["DECR", "fresh"]), int(-1));
+    assert_eq!(client.call(&["GET", "n"]), bulk("1"));
+
+    client.call(&["SET", "text", "abc"]);
+    assert!(is_error(&client.call(&["INCR", "text"]), "ERR"));
+    assert_eq!(client.call(&["GET", "text"]), bulk("abc"));
+
+    client.call(&["SET", "max", "9223372036854775807"]);
+    assert!(is_error(&client.call(&["INCR", "max"]), "ERR"));
+    client.call(&["SET", "min", "-9223372036854775808"]);
+    assert!(is_error(&client.call(&["DECR", "min"]), "ERR"));
+    assert_eq!(client.call(&["GET", "max"]), bulk("9223372036854775807"));
+
+    server.shutdown();
+}
+
+#[test]
+fn incrby_and_decrby_take_any_amount_that_fits() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["INCRBY", "n", "10"]), int(10));
+    assert_eq!(client.call(&["INCRBY", "n", "-15"]), int(-5));
+    assert_eq!(client.call(&["DECRBY", "n", "-5"]), int(0));
+    assert_eq!(
+        client.call(&["INCRBY", "big", "9223372036854775807"]),
+        int(i64::MAX)
+    );
+    assert!(is_error(&client.call(&["INCRBY", "big", "1"]), "ERR"));
+    assert!(is_error(
+        &client.call(&["DECRBY", "n", "-9223372036854775808"]),
+        "ERR"
+    ));
+    assert!(is_error(&client.call(&["INCRBY", "n", "ten"]), "ERR"));
+    assert!(is_error(&client.call(&["INCRBY", "n", "1.5"]), "ERR"));
+    assert_eq!(client.call(&["GET", "n"]), bulk("0"));
+
+    server.shutdown();
+}
+
+#[test]
+fn append_creates_or_extends_a_string() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["APPEND", "s", "Hello"]), int(5));
+    assert_eq!(client.call(&["APPEND", "s", " World"]), int(11));
+    assert_eq!(client.call(&["GET", "s"]), bulk("Hello World"));
+
+    server.shutdown();
+}
+
+#[test]
+fn keys_lists_the_matching_keys_in_order() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    for key in ["hello", "hallo", "hxllo", "world", "a*b"] {
+        client.call(&["SET", key, "1"]);
+    }
+    assert_eq!(
+        client.call(&["KEYS", "*"]),
+        array(&["a*b", "hallo", "hello", "hxllo", "world"])
+    );
+    assert_eq!(
+        client.call(&["KEYS", "h[ae]llo"]),
+        array(&["hallo", "hello"])
+    );
+    assert_eq!(client.call(&["KEYS", "a\\*b"]), array(&["a*b"]));
+    assert_eq!(client.call(&["KEYS", ""]), array(&[]));
+    assert_eq!(client.call(&["KEYS", "nothing*"]), array(&[]));
+
+    server.shutdown();
+}
+
+#[test]
+fn mget_returns_nil_for_absent_keys() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "a", "1"]);
+    client.call(&["SET", "c", "3"]);
+    client.call(&["RPUSH", "list", "x"]);
+    assert_eq!(
+        client.call(&["MGET", "a", "b", "c", "list", "a"]),
+        RespValue::Array(Some(vec![bulk("1"), nil(), bulk("3"), nil(), bulk("1")]))
+    );
+
+    server.shutdown();
+}
+
+#[test]
+fn mset_writes_every_pair_or_refuses_an_odd_count() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "a", "old"]);
+    assert_eq!(client.call(&["MSET", "a", "1", "b", "2"]), ok());
+    assert_eq!(client.call(&["MGET", "a", "b"]), array(&["1", "2"]));
+    assert!(is_error(&client.call(&["MSET", "a", "3", "c"]), "ERR"));
+    assert_eq!(client.call(&["GET", "a"]), bulk("1"));
+    assert_eq!(client.call(&["EXISTS", "c"]), int(0));
+
+    server.shutdown();
+}
+
+#[test]
+fn type_names_every_kind_of_value() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "s", "1"]);
+    client.call(&["RPUSH", "l", "1"]);
+    client.call(&["HSET", "h", "f", "1"]);
+    client.call(&["SADD", "set", "1"]);
+    for (key, kind) in [
+        ("s", "string"),
+        ("l", "list"),
+        ("h", "hash"),
+        ("set", "set"),
+        ("missing", "none"),
+    ] {
+        assert_eq!(
+            client.call(&["TYPE", key]),
+            RespValue::SimpleString(kind.to_string())
+        );
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn commands_on_the_wrong_type_are_refused_with_wrongtype() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "s", "1"]);
+    client.call(&["RPUSH", "l", "1"]);
+    for command in [
+        &["GET", "l"][..],
+        &["APPEND", "l", "x"],
+        &["INCR", "l"],
+        &["RPUSH", "s", "x"],
+        &["LRANGE", "s", "0", "-1"],
+        &["HSET", "s", "f", "v"],
+        &["SADD", "l", "m"],
+    ] {
+        let reply = client.call(command);
+        assert!(
+            is_error(&reply, "WRONGTYPE"),
+            "{:?} gave {:?}",
+            command,
+            reply
+        );
+    }
+    assert_eq!(client.call(&["GET", "s"]), bulk("1"));
+    assert_eq!(client.call(&["LRANGE", "l", "0", "-1"]), array(&["1"]));
+
+    server.shutdown();
+}
+
+#[test]
+fn pushes_and_pops_work_from_both_ends() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["LPUSH", "l", "a", "b"]), int(2));
+    assert_eq!(client.call(&["RPUSH", "l", "c", "d"]), int(4));
+    assert_eq!(
+        client.call(&["LRANGE", "l", "0", "-1"]),
+        array(&["b", "a", "c", "d"])
+    );
+    assert_eq!(client.call(&["LPOP", "l"]), bulk("b"));
+    assert_eq!(client.call(&["RPOP", "l", "2"]), array(&["d", "c"]));
+    assert_eq!(client.call(&["LPOP", "l", "5"]), array(&["a"]));
+    // The emptied list is gone, and popping nothing gives nil
+    assert_eq!(client.call(&["EXISTS", "l"]), int(0));
+    assert_eq!(client.call(&["LPOP", "l"]), nil());
+    assert_eq!(client.call(&["RPOP", "l"]), nil());
+
+    server.shutdown();
+}
+
+#[test]
+fn lrange_clamps_positive_and_negative_indices() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["RPUSH", "l", "a", "b", "c", "d"]);
+    assert_eq!(client.call(&["LRANGE", "l", "1", "2"]), array(&["b", "c"]));
+    assert_eq!(
+        client.call(&["LRANGE", "l", "-2", "-1"]),
+        array(&["c", "d"])
+    );
+    assert_eq!(
+        client.call(&["LRANGE", "l", "-100", "100"]),
+        array(&["a", "b", "c", "d"])
+    );
+    assert_eq!(client.call(&["LRANGE", "l", "5", "10"]), array(&[]));
+    assert_eq!(client.call(&["LRANGE", "l", "2", "1"]), array(&[]));
+    assert_eq!(client.call(&["LRANGE", "missing", "0", "-1"]), array(&[]));
+
+    server.shutdown();
+}
+
+#[test]
+fn hash_fields_are_set_read_and_removed() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["HSET", "h", "a", "1", "b", "2"]), int(2));
+    assert_eq!(client.call(&["HSET", "h", "a", "3", "c", "4"]), int(1));
+    assert_eq!(client.call(&["HGET", "h", "a"]), bulk("3"));
+    assert_eq!(client.call(&["HGET", "h", "missing"]), nil());
+    assert_eq!(client.call(&["HGET", "missing", "a"]), nil());
+    let RespValue::Array(Some(pairs)) = client.call(&["HGETALL", "h"]) else {
+        panic!("HGETALL should reply with an array");
+    };
+    assert_eq!(pairs.len(), 6);
+    assert_eq!(client.call(&["HDEL", "h", "a", "missing"]), int(1));
+    assert_eq!(client.call(&["HDEL", "h", "b", "c"]), int(2));
+    assert_eq!(client.call(&["EXISTS", "h"]), int(0));
+    assert_eq!(client.call(&["HGETALL", "h"]), array(&[]));
+
+    server.shutdown();
+}
+
+#[test]
+fn sets_ignore_duplicates_and_drop_the_key_when_emptied() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["SADD", "s", "b", "a", "b"]), int(2));
+    assert_eq!(client.call(&["SADD", "s", "a", "c"]), int(1));
+    assert_eq!(client.call(&["SMEMBERS", "s"]), array(&["a", "b", "c"]));
+    assert_eq!(client.call(&["SISMEMBER", "s", "a"]), int(1));
+    assert_eq!(client.call(&["SISMEMBER", "s", "z"]), int(0));
+    assert_eq!(client.call(&["SREM", "s", "a", "z"]), int(1));
+    assert_eq!(client.call(&["SREM", "s", "b", "c"]), int(2));
+    assert_eq!(client.call(&["EXISTS", "s"]), int(0));
+    assert_eq!(client.call(&["SMEMBERS", "s"]), array(&[]));
+
+    server.shutdown();
+}
+
+#[test]
+fn silent_connections_are_closed_after_the_idle_timeout() {
+    let config = Config {
+        idle_timeout: Some(Duration::from_secs(1)),
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut silent = Client::connect(addr);
+    let mut active = Client::connect(addr);
+
+    let started = Instant::now();
+    for _ in 0..3 {
+        thread::sleep(Duration::from_millis(400));
+        assert_eq!(active.call(&["PING"]), pong());
+    }
+    assert_eq!(silent.reply(), None);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    // Talking kept the other connection open
+    assert_eq!(active.call(&["PING"]), pong());
+
+    server.shutdown();
+}
+
+#[test]
+fn commands_larger_than_a_read_arrive_whole() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    let value = "v".repeat(200_000);
+    assert_eq!(client.call(&["SET", "big", &value]), ok());
+    assert_eq!(client.call(&["STRLEN", "big"]), int(200_000));
+    assert_eq!(client.call(&["GET", "big"]), bulk(&value));
+
+    server.shutdown();
+}
+
+#[test]
+fn arguments_that_are_not_utf8_are_refused() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.send_raw(b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$2\r\n\xff\xfe\r\n");
+    let reply = client.reply().expect("an error reply");
+    assert!(is_error(&reply, "ERR"), "got {:?}", reply);
+    assert_eq!(client.call(&["EXISTS", "k"]), int(0));
+    // Multi-byte characters that are valid go through untouched
+    assert_eq!(client.call(&["SET", "k", "héllo ✓"]), ok());
+    assert_eq!(client.call(&["GET", "k"]), bulk("héllo ✓"));
+
+    server.shutdown();
+}
+
+#[test]
+fn blank_inline_lines_get_no_reply() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.send_raw(b"\r\n   \n\t\r\nSET a 1\r\n");
+    assert_eq!(client.line(), "OK");
+    client.send_raw(b"\nGET a\n");
+    assert_eq!(client.line(), "1");
+
+    server.shutdown();
+}
+
+#[test]
+fn pipelined_commands_are_answered_in_order() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    let mut batch = Vec::new();
+    for command in [&["SET", "a", "1"][..], &["INCR", "a"], &["GET", "a"]] {
+        let command = RespValue::Array(Some(command.iter().map(|arg| bulk(arg)).collect()));
+        batch.extend(command.encode());
+    }
+    client.send_raw(&batch);
+    assert_eq!(client.reply(), Some(ok()));
+    assert_eq!(client.reply(), Some(int(2)));
+    assert_eq!(client.reply(), Some(bulk("2")));
+
+    client.send_raw(b"SET b 1\r\nGET b\r\n");
+    assert_eq!(client.line(), "OK");
+    assert_eq!(client.line(), "1");
+
+    server.shutdown();
+}
+
+#[test]
+fn command_names_ignore_case() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["set", "a", "1"]), ok());
+    assert_eq!(client.call(&["Get", "a"]), bulk("1"));
+    assert_eq!(client.call(&["iNcR", "a"]), int(2));
+    assert_eq!(client.call(&["RPush", "l", "x"]), int(1));
+    assert_eq!(client.call(&["ping"]), pong());
+    // Keys keep their case
+    assert_eq!(client.call(&["GET", "A"]), nil());
+
+    server.shutdown();
+}
+
+fn text(reply: RespValue) -> String {
+    match reply {
+        RespValue::BulkString(Some(bytes)) => String::from_utf8(bytes).unwrap(),
+        other => panic!("expected a bulk string, got {:?}", other),
+    }
+}
+
+#[test]
+fn help_covers_every_command() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    let help = text(client.call(&["HELP"]));
+    for handler in rustdes::commands::defs::COMMANDS.iter() {
+        assert!(
+            help.contains(&format!("Usage: {}", handler.name())),
+            "HELP doesn't mention {}",
+            handler.name()
+        );
+    }
+    assert!(text(client.call(&["HELP", "get"])).starts_with("Usage: GET <key>"));
+    assert!(is_error(&client.call(&["HELP", "nope"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn unknown_commands_are_reported_as_unknown() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    let reply = client.call(&["FROBNICATE", "a"]);
+    let RespValue::Error(message) = reply else {
+        panic!("expected an error, got {:?}", reply);
+    };
+    assert!(message.contains("Unknown command"), "{}", message);
+    assert_eq!(client.call(&["PING"]), pong());
+
+    server.shutdown();
+}
+
+#[test]
+fn dbsize_counts_only_live_keys() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["DBSIZE"]), int(0));
+    client.call(&["MSET", "a", "1", "b", "2", "c", "3"]);
+    client.call(&["RPUSH", "l", "x"]);
+    assert_eq!(client.call(&["DBSIZE"]), int(4));
+    client.call(&["DEL", "a"]);
+    client.call(&["PEXPIRE", "b", "50"]);
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(client.call(&["DBSIZE"]), int(2));
+
+    server.shutdown();
+}
+
+#[test]
+fn flushall_leaves_an_empty_store_that_still_works() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    for i in 0..50 {
+        client.call(&["SET", &format!("key{}", i), "v"]);
+    }
+    client.call(&["EXPIRE", "key1", "100"]);
+    assert_eq!(client.call(&["FLUSHALL"]), ok());
+    assert_eq!(client.call(&["DBSIZE"]), int(0));
+    assert_eq!(client.call(&["KEYS", "*"]), array(&[]));
+    assert_eq!(client.call(&["SET", "key1", "again"]), ok());
+    assert_eq!(client.call(&["TTL", "key1"]), int(-1));
+
+    server.shutdown();
+}
+
+#[test]
+fn rename_moves_a_key_over_any_existing_one() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "a", "1"]);
+    client.call(&["EXPIRE", "a", "100"]);
+    client.call(&["SET", "b", "2"]);
+    assert_eq!(client.call(&["RENAME", "a", "b"]), ok());
+    assert_eq!(client.call(&["GET", "b"]), bulk("1"));
+    assert_eq!(client.call(&["EXISTS", "a"]), int(0));
+    assert_eq!(client.call(&["TTL", "b"]), int(100));
+
+    assert!(is_error(&client.call(&["RENAME", "missing", "c"]), "ERR"));
+    assert_eq!(client.call(&["RENAME", "b", "b"]), ok());
+    assert_eq!(client.call(&["GET", "b"]), bulk("1"));
+
+    server.shutdown();
+}
+
+#[test]
+fn strlen_counts_bytes() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "ascii", "hello"]);
+    client.call(&["SET", "utf8", "héllo✓"]);
+    assert_eq!(client.call(&["STRLEN", "ascii"]), int(5));
+    assert_eq!(client.call(&["STRLEN", "utf8"]), int(9));
+    assert_eq!(client.call(&["STRLEN", "missing"]), int(0));
+
+    server.shutdown();
+}
+
+#[test]
+fn getset_returns_the_old_value_and_stores_the_new() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["GETSET", "k", "first"]), nil());
+    assert_eq!(client.call(&["GETSET", "k", "second"]), bulk("first"));
+    assert_eq!(client.call(&["GET", "k"]), bulk("second"));
+
+    client.call(&["EXPIRE", "k", "100"]);
+    client.call(&["GETSET", "k", "third"]);
+    assert_eq!(client.call(&["TTL", "k"]), int(-1));
+
+    server.shutdown();
+}
+
+#[test]
+fn setnx_only_creates_and_setex_expires() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["SETNX", "k", "first"]), int(1));
+    assert_eq!(client.call(&["SETNX", "k", "second"]), int(0));
+    assert_eq!(client.call(&["GET", "k"]), bulk("first"));
+
+    assert_eq!(client.call(&["SETEX", "temp", "1", "v"]), ok());
+    assert_eq!(client.call(&["GET", "temp"]), bulk("v"));
+    assert_eq!(client.call(&["TTL", "temp"]), int(1));
+    thread::sleep(Duration::from_millis(1100));
+    assert_eq!(client.call(&["GET", "temp"]), nil());
+    assert!(is_error(&client.call(&["SETEX", "temp", "0", "v"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn each_database_has_its_own_keys() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+    let mut other = Client::connect(addr);
+
+    client.call(&["SET", "k", "zero"]);
+    assert_eq!(client.call(&["SELECT", "1"]), ok());
+    assert_eq!(client.call(&["GET", "k"]), nil());
+    client.call(&["SET", "k", "one"]);
+    // The selection is per connection
+    assert_eq!(other.call(&["GET", "k"]), bulk("zero"));
+    client.call(&["SELECT", "0"]);
+    assert_eq!(client.call(&["GET", "k"]), bulk("zero"));
+    assert!(is_error(&client.call(&["SELECT", "16"]), "ERR"));
+    assert!(is_error(&client.call(&["SELECT", "-1"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn connection_state_carries_over_between_commands() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    let RespValue::Integer(id) = client.call(&["CLIENT", "ID"]) else {
+        panic!("CLIENT ID should be an integer");
+    };
+    client.call(&["SELECT", "2"]);
+    client.call(&["SET", "k", "v"]);
+    let RespValue::Array(Some(fields)) = client.call(&["HELLO"]) else {
+        panic!("HELLO should reply with an array");
+    };
+    assert!(fields.windows(2).any(|pair| pair == [bulk("id"), int(id)]));
+    assert!(
+        fields
+            .windows(2)
+            .any(|pair| pair == [bulk("proto"), int(2)])
+    );
+    // Still in database 2
+    assert_eq!(client.call(&["GET", "k"]), bulk("v"));
+
+    server.shutdown();
+}
+
+#[test]
+fn requirepass_holds_back_commands_until_auth() {
+    let config = Config {
+        requirepass: Some("secret".to_string()),
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+
+    assert!(is_error(&client.call(&["GET", "k"]), "NOAUTH"));
+    assert_eq!(client.call(&["PING"]), pong());
+    assert!(is_error(&client.call(&["AUTH", "wrong"]), "WRONGPASS"));
+    assert!(is_error(&client.call(&["SET", "k", "v"]), "NOAUTH"));
+    assert_eq!(client.call(&["AUTH", "secret"]), ok());
+    assert_eq!(client.call(&["SET", "k", "v"]), ok());
+
+    // Each connection authenticates on its own
+    let mut other = Client::connect(addr);
+    assert!(is_error(&other.call(&["GET", "k"]), "NOAUTH"));
+
+    server.shutdown();
+}
+
+#[test]
+fn published_messages_reach_subscribers() {
+    let (server, addr) = start(&config());
+    let mut subscriber = Client::connect(addr);
+    let mut publisher = Client::connect(addr);
+
+    assert_eq!(
+        subscriber.call(&["SUBSCRIBE", "news"]),
+        RespValue::Array(Some(vec![RespValue::Array(Some(vec![
+            bulk("subscribe"),
+            bulk("news"),
+            int(1)
+        ]))]))
+    );
+    assert_eq!(publisher.call(&["PUBLISH", "news", "hello"]), int(1));
+    assert_eq!(publisher.call(&["PUBLISH", "other", "ignored"]), int(0));
+    assert_eq!(
+        subscriber.reply(),
+        Some(array(&["message", "news", "hello"]))
+    );
+
+    server.shutdown();
+}
+
+#[test]
+fn a_saved_snapshot_is_loaded_on_restart() {
+    let path = temp_path("dump.rdb");
+    let config = Config {
+        snapshot_path: Some(path.clone()),
+        ..config()
+    };
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    client.call(&["SET", "s", "1"]);
+    client.call(&["EXPIRE", "s", "100"]);
+    client.call(&["RPUSH", "l", "a", "b"]);
+    client.call(&["HSET", "h", "f", "v"]);
+    client.call(&["SELECT", "1"]);
+    client.call(&["SADD", "set", "x"]);
+    assert_eq!(client.call(&["SAVE"]), ok());
+    server.shutdown();
+
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+    assert_eq!(client.call(&["GET", "s"]), bulk("1"));
+    assert!(matches!(
+        client.call(&["TTL", "s"]),
+        RespValue::Integer(1..=100)
+    ));
+    assert_eq!(client.call(&["LRANGE", "l", "0", "-1"]), array(&["a", "b"]));
+    assert_eq!(client.call(&["HGET", "h", "f"]), bulk("v"));
+    assert_eq!(client.call(&["DBSIZE"]), int(3));
+    client.call(&["SELECT", "1"]);
+    assert_eq!(client.call(&["SMEMBERS", "set"]), array(&["x"]));
+    server.shutdown();
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn info_reports_clients_and_keyspace() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+    let _other = Client::connect(addr);
+
+    client.call(&["SET", "a", "1"]);
+    client.call(&["SET", "b", "1"]);
+    client.call(&["EXPIRE", "b", "100"]);
+    let info = text(client.call(&["INFO"]));
+    for field in [
+        "# Server",
+        "uptime_in_seconds:",
+        "# Clients",
+        "connected_clients:2",
+        "# Stats",
+        "total_connections_received:2",
+        "# Keyspace",
+        "db0:keys=2,expires=1",
+    ] {
+        assert!(info.contains(field), "INFO lacks {}:\n{}", field, info);
+    }
+    let clients = text(client.call(&["INFO", "clients"]));
+    assert_eq!(clients, "# Clients\nconnected_clients:2");
+
+    server.shutdown();
+}
+
+#[test]
+fn commandstats_count_every_call() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    for _ in 0..3 {
+        client.call(&["SET", "a", "1"]);
+    }
+    client.call(&["GET", "a"]);
+    client.call(&["get", "a"]);
+    let stats = text(client.call(&["INFO", "commandstats"]));
+    assert!(stats.contains("cmdstat_set:calls=3,"), "{}", stats);
+    assert!(stats.contains("cmdstat_get:calls=2,"), "{}", stats);
+    assert!(!stats.contains("cmdstat_incr:"), "{}", stats);
+
+    client.call(&["GET", "a"]);
+    let stats = text(client.call(&["INFO", "commandstats"]));
+    assert!(stats.contains("cmdstat_get:calls=3,"), "{}", stats);
+
+    server.shutdown();
+}
+
+#[test]
+fn connections_past_maxclients_are_refused() {
+    let config = Config {
+        maxclients: 2,
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut first = Client::connect(addr);
+    let mut second = Client::connect(addr);
+    assert_eq!(first.call(&["PING"]), pong());
+    assert_eq!(second.call(&["PING"]), pong());
+
+    let mut refused = Client::connect(addr);
+    assert_eq!(
+        refused.reply(),
+        Some(RespValue::Error(
+            "ERR max number of clients reached".to_string()
+        ))
+    );
+    assert_eq!(refused.reply(), None);
+
+    // A slot opens up once a client leaves
+    drop(first);
+    thread::sleep(Duration::from_millis(200));
+    let mut third = Client::connect(addr);
+    assert_eq!(third.call(&["PING"]), pong());
+
+    server.shutdown();
+}
+
+#[test]
+fn binding_every_interface_accepts_local_connections() {
+    let config = Config {
+        bind: vec!["0.0.0.0".to_string()],
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    assert!(addr.ip().is_unspecified());
+
+    let mut client = Client::connect(SocketAddr::from(([127, 0, 0, 1], addr.port())));
+    assert_eq!(client.call(&["PING"]), pong());
+    server.shutdown();
+
+    let invalid = Config {
+        bind: vec!["not-an-address".to_string()],
+        ..config
+    };
+    let Err(e) = spawn_server(&[0], &invalid) else {
+        panic!("a bad bind address should stop startup");
+    };
+    assert!(e.to_string().contains("not-an-address"), "{}", e);
+}
+
+#[test]
+fn quit_replies_then_closes_the_connection() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.send(&["QUIT"]);
+    client.send(&["SET", "after", "quit"]);
+    assert_eq!(client.reply(), Some(ok()));
+    assert_eq!(client.reply(), None);
+
+    let mut other = Client::connect(addr);
+    assert_eq!(other.call(&["EXISTS", "after"]), int(0));
+
+    server.shutdown();
+}
+
+#[test]
+fn client_list_shows_every_connection() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+    let other = Client::connect(addr);
+    assert_eq!(client.call(&["PING"]), pong());
+
+    let list = text(client.call(&["CLIENT", "LIST"]));
+    let lines: Vec<&str> = list.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", list);
+    for stream in [&client.stream, &other.stream] {
+        let local = stream.local_addr().unwrap();
+        let line = lines
+            .iter()
+            .find(|line| line.contains(&format!(" addr={} ", local)))
+            .unwrap_or_else(|| panic!("{} missing from\n{}", local, list));
+        assert!(line.starts_with("id="), "{}", line);
+        assert!(line.contains(&format!(" laddr={} ", addr)), "{}", line);
+        assert!(
+            line.contains(" age=") && line.contains(" idle="),
+            "{}",
+            line
+        );
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn client_kill_drops_another_connection() {
+    let (server, addr) = start(&config());
+    let mut killer = Client::connect(addr);
+    let mut victim = Client::connect(addr);
+
+    let RespValue::Integer(id) = victim.call(&["CLIENT", "ID"]) else {
+        panic!("CLIENT ID should be an integer");
+    };
+    assert_eq!(killer.call(&["CLIENT", "KILL", &id.to_string()]), ok());
+    assert_eq!(victim.reply(), None);
+    assert!(is_error(&killer.call(&["CLIENT", "KILL", "999999"]), "ERR"));
+    assert_eq!(killer.call(&["PING"]), pong());
+
+    server.shutdown();
+}
+
+#[test]
+fn the_reaper_closes_connections_that_went_quiet() {
+    let config = Config {
+        reap_idle_after: Some(Duration::from_secs(1)),
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut quiet = Client::connect(addr);
+    let mut busy = Client::connect(addr);
+
+    // One command, then silence
+    assert_eq!(quiet.call(&["PING"]), pong());
+    let started = Instant::now();
+    for _ in 0..4 {
+        thread::sleep(Duration::from_millis(400));
+        assert_eq!(busy.call(&["PING"]), pong());
+    }
+    assert_eq!(quiet.reply(), None);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(busy.call(&["PING"]), pong());
+
+    server.shutdown();
+}
+
+#[test]
+fn pexpire_and_pttl_work_in_milliseconds() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "k", "v"]);
+    assert_eq!(client.call(&["PTTL", "k"]), int(-1));
+    assert_eq!(client.call(&["PTTL", "missing"]), int(-2));
+    assert_eq!(client.call(&["PEXPIRE", "missing", "100"]), int(0));
+
+    assert_eq!(client.call(&["PEXPIRE", "k", "1500"]), int(1));
+    let RespValue::Integer(left) = client.call(&["PTTL", "k"]) else {
+        panic!("PTTL should be an integer");
+    };
+    assert!((1400..=1500).contains(&left), "{}", left);
+    // TTL reads the same deadline, rounded to seconds
+    assert!(matches!(
+        client.call(&["TTL", "k"]),
+        RespValue::Integer(1 | 2)
+    ));
+
+    assert_eq!(client.call(&["PEXPIRE", "k", "150"]), int(1));
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(client.call(&["GET", "k"]), bulk("v"));
+    thread::sleep(Duration::from_millis(150));
+    assert_eq!(client.call(&["GET", "k"]), nil());
+    assert_eq!(client.call(&["PTTL", "k"]), int(-2));
+
+    server.shutdown();
+}
+
+#[test]
+fn persist_removes_only_an_existing_timeout() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "timed", "v"]);
+    client.call(&["EXPIRE", "timed", "100"]);
+    client.call(&["SET", "plain", "v"]);
+
+    assert_eq!(client.call(&["PERSIST", "timed"]), int(1));
+    assert_eq!(client.call(&["TTL", "timed"]), int(-1));
+    assert_eq!(client.call(&["GET", "timed"]), bulk("v"));
+    assert_eq!(client.call(&["PERSIST", "timed"]), int(0));
+    assert_eq!(client.call(&["PERSIST", "plain"]), int(0));
+    assert_eq!(client.call(&["PERSIST", "missing"]), int(0));
+
+    server.shutdown();
+}
+
+#[test]
+fn readers_and_writers_run_side_by_side() {
+    let (server, addr) = start(&config());
+    let mut setup = Client::connect(addr);
+    for i in 0..20 {
+        setup.call(&["SET", &format!("key{}", i), "0"]);
+    }
+    // Some keys expire while being read, so reads also clean up
+    for i in 0..5 {
+        setup.call(&["PEXPIRE", &format!("key{}", i), "100"]);
+    }
+
+    let readers: Vec<_> = (0..8)
+        .map(|_| {
+            thread::spawn(move || {
+                let mut client = Client::connect(addr);
+                for round in 0..200 {
+                    let key = format!("key{}", round % 20);
+                    match client.call(&["GET", &key]) {
+                        RespValue::BulkString(Some(value)) => {
+                            let value: i64 = String::from_utf8(value).unwrap().parse().unwrap();
+                            assert!(value >= 0);
+                        }
+                        RespValue::BulkString(None) => assert!(round % 20 < 5, "{} vanished", key),
+                        other => panic!("unexpected reply {:?}", other),
+                    }
+                }
+            })
+        })
+        .collect();
+    let writers: Vec<_> = (0..2)
+        .map(|_| {
+            thread::spawn(move || {
+                let mut client = Client::connect(addr);
+                for round in 0..200 {
+                    let key = format!("key{}", 5 + round % 15);
+                    assert!(matches!(
+                        client.call(&["INCR", &key]),
+                        RespValue::Integer(_)
+                    ));
+                }
+            })
+        })
+        .collect();
+    for handle in readers.into_iter().chain(writers) {
+        handle.join().unwrap();
+    }
+
+    let mut total = 0;
+    for i in 5..20 {
+        let RespValue::BulkString(Some(value)) = setup.call(&["GET", &format!("key{}", i)]) else {
+            panic!("key{} should exist", i);
+        };
+        total += String::from_utf8(value).unwrap().parse::<i64>().unwrap();
+    }
+    assert_eq!(total, 400);
+    thread::sleep(Duration::from_millis(150));
+    assert_eq!(setup.call(&["DBSIZE"]), int(15));
+
+    server.shutdown();
+}
+
+#[test]
+fn scan_visits_every_key() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+    for i in 0..250 {
+        client.call(&["SET", &format!("key{}", i), "v"]);
+    }
+    client.call(&["SET", "other", "v"]);
+
+    let mut seen = std::collections::HashSet::new();
+    let mut matched = 0;
+    let mut cursor = "0".to_string();
+    loop {
+        let RespValue::Array(Some(reply)) =
+            client.call(&["SCAN", &cursor, "MATCH", "key*", "COUNT", "7"])
+        else {
+            panic!("SCAN should reply with an array");
+        };
+        let [
+            RespValue::BulkString(Some(next)),
+            RespValue::Array(Some(keys)),
+        ] = &reply[..]
+        else {
+            panic!("unexpected SCAN reply {:?}", reply);
+        };
+        for key in keys {
+            let RespValue::BulkString(Some(key)) = key else {
+                panic!("keys should be bulk strings");
+            };
+            seen.insert(String::from_utf8(key.clone()).unwrap());
+            matched += 1;
+        }
+        cursor = String::from_utf8(next.clone()).unwrap();
+        if cursor == "0" {
+            break;
+        }
+    }
+    assert_eq!(seen.len(), 250);
+    assert_eq!(matched, 250);
+    assert!(!seen.contains("other"));
+    assert!(is_error(&client.call(&["SCAN", "0", "COUNT", "0"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn copy_refuses_an_existing_destination_without_replace() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["RPUSH", "source", "a", "b"]);
+    client.call(&["EXPIRE", "source", "100"]);
+    assert_eq!(client.call(&["COPY", "source", "copy"]), int(1));
+    assert_eq!(
+        client.call(&["LRANGE", "copy", "0", "-1"]),
+        array(&["a", "b"])
+    );
+    assert_eq!(client.call(&["TTL", "copy"]), int(100));
+    // The copy is its own value
+    client.call(&["RPUSH", "copy", "c"]);
+    assert_eq!(client.call(&["LLEN", "source"]), int(2));
+
+    client.call(&["SET", "taken", "v"]);
+    assert_eq!(client.call(&["COPY", "source", "taken"]), int(0));
+    assert_eq!(client.call(&["GET", "taken"]), bulk("v"));
+    assert_eq!(client.call(&["COPY", "source", "taken", "REPLACE"]), int(1));
+    assert_eq!(
+        client.call(&["TYPE", "taken"]),
+        RespValue::SimpleString("list".to_string())
+    );
+
+    assert_eq!(client.call(&["COPY", "missing", "x"]), int(0));
+
+    server.shutdown();
+}
+
+#[test]
+fn randomkey_eventually_returns_every_key() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["RANDOMKEY"]), nil());
+    client.call(&["MSET", "a", "1", "b", "2", "c", "3", "d", "4"]);
+    client.call(&["SET", "gone", "v"]);
+    client.call(&["PEXPIRE", "gone", "1"]);
+    thread::sleep(Duration::from_millis(20));
+
+    let mut seen = std::collections::HashSet::new();
+    for _ in 0..200 {
+        let RespValue::BulkString(Some(key)) = client.call(&["RANDOMKEY"]) else {
+            panic!("RANDOMKEY should return a key");
+        };
+        seen.insert(String::from_utf8(key).unwrap());
+    }
+    let mut seen: Vec<String> = seen.into_iter().collect();
+    seen.sort();
+    assert_eq!(seen, ["a", "b", "c", "d"]);
+
+    server.shutdown();
+}
+
+#[test]
+fn touch_counts_keys_and_keeps_them_from_eviction() {
+    let (server, addr) = start(&limited(EvictionPolicy::AllKeysLru));
+    let mut client = Client::connect(addr);
+
+    for key in ["a", "b", "c"] {
+        client.call(&["SET", key, "1"]);
+    }
+    assert_eq!(client.call(&["TOUCH", "a", "a", "missing"]), int(2));
+
+    // a was used last, so b goes first
+    client.call(&["SET", "d", "1"]);
+    assert!(exists(&mut client, "a"));
+    assert!(!exists(&mut client, "b"));
+
+    client.call(&["PEXPIRE", "c", "1"]);
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(client.call(&["TOUCH", "c", "d"]), int(1));
+
+    server.shutdown();
+}
+
+#[test]
+fn config_set_changes_runtime_settings_only() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(
+        client.call(&["CONFIG", "GET", "maxclients"]),
+        array(&["maxclients", "10000"])
+    );
+    assert_eq!(client.call(&["CONFIG", "SET", "maxclients", "50"]), ok());
+    assert_eq!(
+        client.call(&["CONFIG", "GET", "MAXCLIENTS"]),
+        array(&["maxclients", "50"])
+    );
+    assert!(is_error(
+        &client.call(&["CONFIG", "SET", "maxclients", "none"]),
+        "ERR"
+    ));
+    assert!(is_error(
+        &client.call(&["CONFIG", "SET", "bind", "0.0.0.0"]),
+        "ERR"
+    ));
+    assert!(is_error(
+        &client.call(&["CONFIG", "SET", "colour", "blue"]),
+        "ERR"
+    ));
+    assert_eq!(
+        client.call(&["CONFIG", "GET", "bind"]),
+        array(&["bind", "127.0.0.1"])
+    );
+
+    server.shutdown();
+}
+
+#[test]
+fn a_pipeline_is_answered_in_one_go() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    let mut batch = Vec::new();
+    for i in 0..100 {
+        let key = format!("key{}", i);
+        let command = RespValue::Array(Some(vec![bulk("SET"), bulk(&key), bulk("v")]));
+        batch.extend(command.encode());
+    }
+    batch.extend(RespValue::Array(Some(vec![bulk("DBSIZE")])).encode());
+    client.send_raw(&batch);
+    for _ in 0..100 {
+        assert_eq!(client.reply(), Some(ok()));
+    }
+    assert_eq!(client.reply(), Some(int(100)));
+
+    // Nothing was left behind in the buffer once the batch was answered
+    assert_eq!(client.call(&["PING"]), pong());
+
+    server.shutdown();
+}
+
+#[test]
+fn failures_are_error_frames_in_both_protocols() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["RPUSH", "list", "x"]);
+    let RespValue::Error(unknown) = client.call(&["NOPE"]) else {
+        panic!("an unknown command should be an error frame");
+    };
+    assert!(unknown.starts_with("ERR "), "{}", unknown);
+    let RespValue::Error(wrongtype) = client.call(&["GET", "list"]) else {
+        panic!("WRONGTYPE should be an error frame");
+    };
+    assert!(wrongtype.starts_with("WRONGTYPE "), "{}", wrongtype);
+
+    // Inline replies put the same `-` in front of the code
+    client.send_raw(b"NOPE\r\nGET list\r\n");
+    assert!(client.line().starts_with("-ERR "));
+    assert!(client.line().starts_with("-WRONGTYPE "));
+
+    server.shutdown();
+}
+
+#[test]
+fn command_reports_arity_from_the_parsers() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(
+        client.call(&["COMMAND", "COUNT"]),
+        int(rustdes::commands::defs::COMMANDS.len() as i64)
+    );
+    assert_eq!(
+        client.call(&["COMMAND", "INFO", "ping", "GET"]),
+        array(&["name=PING min=0 max=-1", "name=GET min=1 max=1"])
+    );
+    assert!(is_error(&client.call(&["COMMAND", "INFO", "nope"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn hello_describes_the_server_and_checks_the_version() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    let RespValue::Array(Some(fields)) = client.call(&["HELLO"]) else {
+        panic!("HELLO should reply with an array");
+    };
+    let names: Vec<RespValue> = fields.iter().step_by(2).cloned().collect();
+    assert_eq!(
+        names,
+        ["server", "version", "proto", "id", "mode", "role"].map(bulk)
+    );
+    assert_eq!(fields[1], bulk("rustdes"));
+    assert_eq!(fields[5], int(2));
+
+    let RespValue::Array(Some(fields)) = client.call(&["HELLO", "2"]) else {
+        panic!("HELLO 2 should reply with an array");
+    };
+    assert_eq!(fields[5], int(2));
+    assert!(is_error(&client.call(&["HELLO", "4"]), "NOPROTO"));
+    assert!(is_error(&client.call(&["HELLO", "two"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn client_id_is_stable_and_unique() {
+    let (server, addr) = start(&config());
+    let mut first = Client::connect(addr);
+    let mut second = Client::connect(addr);
+
+    let id = first.call(&["CLIENT", "ID"]);
+    assert!(matches!(id, RespValue::Integer(_)));
+    assert_eq!(first.call(&["CLIENT", "ID"]), id);
+    assert_ne!(second.call(&["CLIENT", "ID"]), id);
+
+    server.shutdown();
+}
+
+#[test]
+fn a_panicking_command_gets_an_error_and_the_connection_stays_open() {
+    let config = Config {
+        enable_debug_command: true,
+        ..config()
+    };
+    let (server, addr) = start(&config);
+    let mut client = Client::connect(addr);
+
+    for _ in 0..2 {
+        assert!(is_error(&client.call(&["DEBUG", "PANIC"]), "ERR"));
+        assert_eq!(client.call(&["PING"]), pong());
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn object_encoding_follows_the_value() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "number", "12345"]);
+    client.call(&["SET", "short", "hello"]);
+    client.call(&["SET", "long", &"x".repeat(100)]);
+    client.call(&["RPUSH", "list", "a"]);
+    assert_eq!(client.call(&["OBJECT", "ENCODING", "number"]), bulk("int"));
+    assert_eq!(
+        client.call(&["OBJECT", "ENCODING", "short"]),
+        bulk("embstr")
+    );
+    assert_eq!(client.call(&["OBJECT", "ENCODING", "long"]), bulk("raw"));
+    assert_eq!(
+        client.call(&["OBJECT", "ENCODING", "list"]),
+        bulk("listpack")
+    );
+    assert!(is_error(
+        &client.call(&["OBJECT", "ENCODING", "missing"]),
+        "ERR"
+    ));
+
+    server.shutdown();
+}
+
+#[test]
+fn getrange_clamps_and_setrange_pads() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SET", "k", "Hello, world"]);
+    assert_eq!(client.call(&["GETRANGE", "k", "0", "4"]), bulk("Hello"));
+    assert_eq!(client.call(&["GETRANGE", "k", "-5", "-1"]), bulk("world"));
+    assert_eq!(client.call(&["GETRANGE", "k", "7", "100"]), bulk("world"));
+    assert_eq!(client.call(&["GETRANGE", "k", "5", "2"]), bulk(""));
+    assert_eq!(client.call(&["GETRANGE", "k", "50", "60"]), bulk(""));
+    assert_eq!(client.call(&["GETRANGE", "missing", "0", "-1"]), bulk(""));
+
+    assert_eq!(client.call(&["SETRANGE", "k", "7", "there"]), int(12));
+    assert_eq!(client.call(&["GET", "k"]), bulk("Hello, there"));
+    assert_eq!(client.call(&["SETRANGE", "padded", "3", "x"]), int(4));
+    assert_eq!(client.call(&["GET", "padded"]), bulk("\0\0\0x"));
+    assert!(is_error(&client.call(&["SETRANGE", "k", "-1", "x"]), "ERR"));
+
+    server.shutdown();
+}
+
+#[test]
+fn inline_commands_may_end_in_crlf_or_lf() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.send_raw(b"PING\r\n");
+    assert_eq!(client.line(), "PONG");
+    client.send_raw(b"SET k v\r\nGET k\n");
+    assert_eq!(client.line(), "OK");
+    // The \r doesn't end up in the value
+    assert_eq!(client.line(), "v");
+
+    server.shutdown();
+}
+
+#[test]
+fn collection_sizes_are_counted() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["RPUSH", "list", "a", "b", "c"]);
+    client.call(&["HSET", "hash", "f1", "v", "f2", "v"]);
+    client.call(&["SADD", "set", "x"]);
+    client.call(&["SET", "string", "v"]);
+
+    for (command, key, size) in [
+        ("LLEN", "list", 3),
+        ("HLEN", "hash", 2),
+        ("SCARD", "set", 1),
+    ] {
+        assert_eq!(client.call(&[command, key]), int(size), "{}", command);
+        assert_eq!(client.call(&[command, "missing"]), int(0), "{}", command);
+        assert!(
+            is_error(&client.call(&[command, "string"]), "WRONGTYPE"),
+            "{}",
+            command
+        );
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn hincrby_counts_from_zero_and_refuses_non_integers() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    assert_eq!(client.call(&["HINCRBY", "h", "n", "5"]), int(5));
+    assert_eq!(client.call(&["HINCRBY", "h", "n", "-7"]), int(-2));
+    assert_eq!(client.call(&["HGET", "h", "n"]), bulk("-2"));
+
+    client.call(&["HSET", "h", "text", "abc"]);
+    assert!(is_error(
+        &client.call(&["HINCRBY", "h", "text", "1"]),
+        "ERR"
+    ));
+    client.call(&["HSET", "h", "big", &i64::MAX.to_string()]);
+    assert!(is_error(&client.call(&["HINCRBY", "h", "big", "1"]), "ERR"));
+    client.call(&["SET", "s", "1"]);
+    assert!(is_error(
+        &client.call(&["HINCRBY", "s", "n", "1"]),
+        "WRONGTYPE"
+    ));
+
+    server.shutdown();
+}
+
+#[test]
+fn lindex_and_lset_use_positive_and_negative_indices() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["RPUSH", "l", "a", "b", "c"]);
+    assert_eq!(client.call(&["LINDEX", "l", "0"]), bulk("a"));
+    assert_eq!(client.call(&["LINDEX", "l", "-1"]), bulk("c"));
+    assert_eq!(client.call(&["LINDEX", "l", "3"]), nil());
+    assert_eq!(client.call(&["LINDEX", "l", "-4"]), nil());
+    assert_eq!(client.call(&["LINDEX", "missing", "0"]), nil());
+
+    assert_eq!(client.call(&["LSET", "l", "1", "B"]), ok());
+    assert_eq!(client.call(&["LSET", "l", "-1", "C"]), ok());
+    assert_eq!(
+        client.call(&["LRANGE", "l", "0", "-1"]),
+        array(&["a", "B", "C"])
+    );
+    assert!(is_error(&client.call(&["LSET", "l", "3", "x"]), "ERR"));
+    assert!(is_error(
+        &client.call(&["LSET", "missing", "0", "x"]),
+        "ERR"
+    ));
+
+    server.shutdown();
+}
+
+#[test]
+fn set_operations_combine_sets_in_order() {
+    let (server, addr) = start(&config());
+    let mut client = Client::connect(addr);
+
+    client.call(&["SADD", "a", "1", "2", "3", "4"]);
+    client.call(&["SADD", "b", "3", "4", "5"]);
+    client.call(&["SADD", "c", "4", "6"]);
+    client.call(&["SET", "s", "v"]);
+
+    assert_eq!(client.call(&["SINTER", "a", "b"]), array(&["3", "4"]));
+    assert_eq!(client.call(&["SINTER", "a", "b", "c"]), array(&["4"]));
+    assert_eq!(client.call(&["SINTER", "a", "missing"]), array(&[]));
+    assert_eq!(
+        client.call(&["SUNION", "a", "b", "missing"]),
+        array(&["1", "2", "3", "4", "5"])
+    );
+    assert_eq!(client.call(&["SDIFF", "a", "b", "c"]), array(&["1", "2"]));
+    assert_eq!(client.call(&["SDIFF", "b", "a"]), array(&["5"]));
+    assert_eq!(client.call(&["SDIFF", "missing", "a"]), array(&[]));
+    for command in ["SINTER", "SUNION", "SDIFF"] {
+        assert!(
+            is_error(&client.call(&[command, "a", "s"]), "WRONGTYPE"),
+            "{}",
+            command
+        );
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn every_bind_address_gets_a_listener_on_the_same_store() {
+    let config = Config {
+        bind: vec!["127.0.0.1".to_string(), "127.0.0.2".to_string()],
+        ..config()
+    };
+    let server = spawn_server(&[0], &config).expect("server should start");
+    let addrs = server.local_addrs().to_vec();
+    let ips: Vec<String> = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+    assert_eq!(ips, ["127.0.0.1", "127.0.0.2"]);
+
+    let mut first = Client::connect(addrs[0]);
+    let mut second = Client::connect(addrs[1]);
+    assert_eq!(first.call(&["SET", "shared", "1"]), ok());
+    assert_eq!(second.call(&["GET", "shared"]), bulk("1"));
+    assert_eq!(second.call(&["INCR", "shared"]), int(2));
+    assert_eq!(first.call(&["GET", "shared"]), bulk("2"));
+
+    server.shutdown();
+}